@@ -0,0 +1,681 @@
+//! Parsing of the `#[inspect(...)]` attributes
+
+use {
+    proc_macro2::{Span, TokenStream},
+    quote::{quote, quote_spanned, ToTokens},
+    syn::{
+        meta::ParseNestedMeta, parse::Parse, punctuated::Punctuated, spanned::Spanned, Attribute,
+        Data, Expr, LitInt, LitStr, Path, Token, WherePredicate,
+    },
+};
+
+#[derive(Default)]
+pub enum FieldInspectKind {
+    /// Auto-inspected (it is assumed that the field implements Inspect)
+    #[default]
+    Auto,
+    /// The `inspect` and `inspect_mut` functions of the module or type named by the path
+    /// are called to inspect the field, like serde's `with`.
+    /// They take (thing: &T, ui: &mut Ui, ctx: &InspectCtx) and
+    /// (thing: &mut T, ui: &mut Ui, ctx: &InspectCtx).
+    With(Path),
+    /// A function named by the path is called to inspect the field mutably.
+    /// The function takes (thing: &mut T, ui: &mut Ui, ctx: &InspectCtx).
+    /// Only used by the legacy `#[inspect_with(...)]` attribute.
+    WithFn(Path),
+    /// Not visited, only the name of the field is shown.
+    /// Useful when you want to skip a field that doesn't implement Inspect.
+    Opaque,
+    /// Not shown at all.
+    Skip,
+    /// The fields of the field are shown in place of the field, through `InspectFields`.
+    Flatten,
+    /// The field is an enum, inspected mutably with the given variant selector
+    Enum(EnumDisplay),
+}
+
+/// How the variant of an enum is selected, mirroring `egui_inspect::EnumDisplay`
+#[derive(Clone, Copy)]
+pub enum EnumDisplay {
+    Combo,
+    Radio,
+    Toggle,
+}
+
+impl EnumDisplay {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let lit: LitStr = meta.value()?.parse()?;
+        match lit.value().as_str() {
+            "combo" => Ok(Self::Combo),
+            "radio" => Ok(Self::Radio),
+            "toggle" => Ok(Self::Toggle),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "expected `\"combo\"`, `\"radio\"` or `\"toggle\"`",
+            )),
+        }
+    }
+}
+
+impl ToTokens for EnumDisplay {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            Self::Combo => quote! { ::egui_inspect::EnumDisplay::Combo },
+            Self::Radio => quote! { ::egui_inspect::EnumDisplay::Radio },
+            Self::Toggle => quote! { ::egui_inspect::EnumDisplay::Toggle },
+        });
+    }
+}
+
+/// Options of a field, parsed from its attributes
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub kind: FieldInspectKind,
+    /// Label to show instead of the field name
+    pub rename: Option<LitStr>,
+    /// Hover text to show instead of the doc comment
+    pub tooltip: Option<LitStr>,
+    /// Inspect with `Inspect::inspect`, even when inspecting mutably
+    pub read_only: bool,
+    pub num: NumOpts,
+    /// Replaces the inferred bounds required by this field
+    pub bound: Option<Bound>,
+    /// Name of the group the field is shown in
+    pub group: Option<LitStr>,
+    /// Position of the field among the others
+    pub order: Option<i32>,
+    /// Condition for showing the field
+    pub visible_if: Option<Expr>,
+    /// Condition for being able to interact with the field
+    pub enabled_if: Option<Expr>,
+    /// Method to call when the field changed
+    pub on_change: Option<Expr>,
+    /// Function checking the value of the field
+    pub validate: Option<Expr>,
+    /// The option that determined `kind`, used for reporting conflicts
+    kind_option: Option<(&'static str, Span)>,
+    read_only_span: Option<Span>,
+}
+
+impl FieldAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut field_attrs = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("opaque") {
+                attr.meta.require_path_only()?;
+                field_attrs.set_kind(FieldInspectKind::Opaque, "opaque", attr.path().span())?;
+            } else if attr.path().is_ident("inspect_with") {
+                let fun = attr.parse_args().map_err(expected_fn_path)?;
+                field_attrs.set_kind(
+                    FieldInspectKind::WithFn(fun),
+                    "inspect_with",
+                    attr.path().span(),
+                )?;
+            } else if attr.path().is_ident("inspect") {
+                attr.parse_nested_meta(|meta| field_attrs.parse_meta(meta))?;
+            }
+        }
+        field_attrs.check()?;
+        Ok(field_attrs)
+    }
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        let span = meta.path.span();
+        if meta.path.is_ident("rename") {
+            set_once(&mut self.rename, &meta)?;
+        } else if meta.path.is_ident("tooltip") {
+            set_once(&mut self.tooltip, &meta)?;
+        } else if meta.path.is_ident("read_only") {
+            if self.read_only {
+                return Err(meta.error("duplicate `read_only` option"));
+            }
+            self.read_only = true;
+            self.read_only_span = Some(span);
+        } else if meta.path.is_ident("bound") {
+            if self.bound.is_some() {
+                return Err(meta.error("duplicate `bound` option"));
+            }
+            self.bound = Some(parse_bound(&meta)?);
+        } else if meta.path.is_ident("visible_if") {
+            set_once(&mut self.visible_if, &meta)?;
+        } else if meta.path.is_ident("enabled_if") {
+            set_once(&mut self.enabled_if, &meta)?;
+        } else if meta.path.is_ident("on_change") {
+            set_once(&mut self.on_change, &meta)?;
+        } else if meta.path.is_ident("validate") {
+            set_once(&mut self.validate, &meta)?;
+        } else if meta.path.is_ident("group") {
+            set_once(&mut self.group, &meta)?;
+        } else if meta.path.is_ident("order") {
+            if self.order.is_some() {
+                return Err(meta.error("duplicate `order` option"));
+            }
+            let input = meta.value()?;
+            let neg: Option<Token![-]> = input.parse()?;
+            let order: i32 = input.parse::<LitInt>()?.base10_parse()?;
+            self.order = Some(if neg.is_some() { -order } else { order });
+        } else if meta.path.is_ident("skip") {
+            self.set_kind(FieldInspectKind::Skip, "skip", span)?;
+        } else if meta.path.is_ident("opaque") {
+            self.set_kind(FieldInspectKind::Opaque, "opaque", span)?;
+        } else if meta.path.is_ident("flatten") {
+            self.set_kind(FieldInspectKind::Flatten, "flatten", span)?;
+        } else if meta.path.is_ident("with") {
+            let module = meta.value()?.parse().map_err(|e: syn::Error| {
+                syn::Error::new(
+                    e.span(),
+                    "expected a path to a module or type with inspect functions",
+                )
+            })?;
+            self.set_kind(FieldInspectKind::With(module), "with", span)?;
+        } else if meta.path.is_ident("display") {
+            let display = EnumDisplay::parse(&meta)?;
+            self.set_kind(FieldInspectKind::Enum(display), "display", span)?;
+        } else if !self.num.parse_meta(&meta)? {
+            return Err(meta.error(format_args!(
+                "unknown inspect attribute `{}`",
+                meta.path.to_token_stream().to_string().replace(' ', "")
+            )));
+        }
+        Ok(())
+    }
+    fn set_kind(
+        &mut self,
+        kind: FieldInspectKind,
+        option: &'static str,
+        span: Span,
+    ) -> syn::Result<()> {
+        if let Some((prev, _)) = self.kind_option {
+            return Err(syn::Error::new(
+                span,
+                format!("`{option}` can't be used together with `{prev}`"),
+            ));
+        }
+        self.kind = kind;
+        self.kind_option = Some((option, span));
+        Ok(())
+    }
+    /// Reports options that don't make sense together
+    fn check(&self) -> syn::Result<()> {
+        if let Some(on_change) = &self.on_change {
+            let unchangeable = match (&self.kind, self.kind_option) {
+                (FieldInspectKind::Opaque | FieldInspectKind::Skip, Some((option, _))) => {
+                    Some(option)
+                }
+                _ if self.read_only => Some("read_only"),
+                _ => None,
+            };
+            if let Some(option) = unchangeable {
+                return Err(syn::Error::new_spanned(
+                    on_change,
+                    format!("`on_change` has no effect together with `{option}`"),
+                ));
+            }
+        }
+        if let (Some(num_span), Some((option, _))) = (self.num.span, self.kind_option) {
+            return Err(syn::Error::new(
+                num_span,
+                format!("numeric options can't be used together with `{option}`"),
+            ));
+        }
+        if let (Some(span), FieldInspectKind::WithFn(_)) = (self.read_only_span, &self.kind) {
+            return Err(syn::Error::new(
+                span,
+                "`read_only` can't be used together with a custom inspect function",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn expected_fn_path(e: syn::Error) -> syn::Error {
+    syn::Error::new(e.span(), "expected a path to an inspect function")
+}
+
+/// Parses the value of `meta` into `slot`, reporting an error if it was already set
+fn set_once<T: Parse>(slot: &mut Option<T>, meta: &ParseNestedMeta) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(meta.error(format_args!(
+            "duplicate `{}` option",
+            meta.path.to_token_stream()
+        )));
+    }
+    *slot = Some(meta.value()?.parse()?);
+    Ok(())
+}
+
+/// Where predicates given with `#[inspect(bound = "...")]`
+pub type Bound = Punctuated<WherePredicate, Token![,]>;
+
+fn parse_bound(meta: &ParseNestedMeta) -> syn::Result<Bound> {
+    let lit: LitStr = meta.value()?.parse()?;
+    lit.parse_with(Punctuated::parse_terminated)
+}
+
+/// How a struct is shown inside its parent
+#[derive(Default, PartialEq)]
+pub enum Layout {
+    /// The fields are shown inside a collapsing header
+    #[default]
+    Header,
+    /// The fields are shown directly, one below the other
+    NoHeader,
+    /// The fields are shown directly, on a single line
+    Inline,
+}
+
+/// Options of a struct or enum, parsed from its attributes
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// Replaces all the inferred bounds of the generated impl
+    pub bound: Option<Bound>,
+    /// Header text to show instead of the type name
+    pub title: Option<LitStr>,
+    /// Format string for the text of the collapsed header
+    pub summary: Option<LitStr>,
+    /// Whether the header starts out expanded
+    pub default_open: bool,
+    pub layout: Layout,
+    /// Show the field groups as tabs
+    pub tabs: bool,
+    /// Function checking the struct as a whole
+    pub validate: Option<Expr>,
+    /// Buttons calling `&mut self` methods, with their labels
+    pub buttons: Vec<(LitStr, Expr)>,
+    /// Virtual fields, shown after the real ones
+    pub properties: Vec<Property>,
+    /// The foreign type that the struct mirrors
+    pub remote: Option<Path>,
+    /// Show every member of a union, reinterpreting its bytes
+    pub unsafe_view_all_members: bool,
+    /// Function returning the name of the member of a union that is in use
+    pub active_member: Option<Expr>,
+    /// Variant selector of an enum
+    pub display: Option<EnumDisplay>,
+    /// Show the discriminants of the variants of a fieldless enum
+    pub show_discriminant: bool,
+    /// The struct-only options that were given, used for reporting errors
+    struct_options: Vec<(&'static str, Span)>,
+}
+
+impl ContainerAttrs {
+    pub fn from_attrs(attrs: &[Attribute], data: &Data) -> syn::Result<Self> {
+        let mut container_attrs = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("inspect") {
+                attr.parse_nested_meta(|meta| container_attrs.parse_meta(meta, data))?;
+            }
+        }
+        container_attrs.check(data)?;
+        Ok(container_attrs)
+    }
+    fn parse_meta(&mut self, meta: ParseNestedMeta, data: &Data) -> syn::Result<()> {
+        let span = meta.path.span();
+        if meta.path.is_ident("bound") {
+            if self.bound.is_some() {
+                return Err(meta.error("duplicate `bound` option"));
+            }
+            self.bound = Some(parse_bound(&meta)?);
+            return Ok(());
+        }
+        if meta.path.is_ident("button") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let label = content.parse()?;
+            content.parse::<Token![,]>()?;
+            let fun = content.parse()?;
+            content.parse::<Option<Token![,]>>()?;
+            self.buttons.push((label, fun));
+            // Unlike the other options, any number of buttons can be given
+            self.struct_options.push(("button", span));
+            return Ok(());
+        }
+        if meta.path.is_ident("property") {
+            self.properties.push(Property::parse(&meta)?);
+            self.struct_options.push(("property", span));
+            return Ok(());
+        }
+        if meta.path.is_ident("display") || meta.path.is_ident("show_discriminant") {
+            let Data::Enum(e) = data else {
+                return Err(meta.error(format_args!(
+                    "`{}` is only supported on enums",
+                    meta.path.to_token_stream()
+                )));
+            };
+            if meta.path.is_ident("display") {
+                if self.display.is_some() {
+                    return Err(meta.error("duplicate `display` option"));
+                }
+                self.display = Some(EnumDisplay::parse(&meta)?);
+            } else if self.show_discriminant {
+                return Err(meta.error("duplicate `show_discriminant` option"));
+            } else if e.variants.iter().any(|var| !var.fields.is_empty()) {
+                return Err(
+                    meta.error("`show_discriminant` is only supported on enums without fields")
+                );
+            } else {
+                self.show_discriminant = true;
+            }
+            return Ok(());
+        }
+        if meta.path.is_ident("unsafe_view_all_members") || meta.path.is_ident("active_member") {
+            if !matches!(data, Data::Union(_)) {
+                return Err(meta.error(format_args!(
+                    "`{}` is only supported on unions",
+                    meta.path.to_token_stream()
+                )));
+            }
+            if meta.path.is_ident("active_member") {
+                set_once(&mut self.active_member, &meta)?;
+            } else if self.unsafe_view_all_members {
+                return Err(meta.error("duplicate `unsafe_view_all_members` option"));
+            } else {
+                self.unsafe_view_all_members = true;
+            }
+            return Ok(());
+        }
+        let option = if meta.path.is_ident("title") {
+            set_once(&mut self.title, &meta)?;
+            "title"
+        } else if meta.path.is_ident("summary") {
+            set_once(&mut self.summary, &meta)?;
+            "summary"
+        } else if meta.path.is_ident("default_open") {
+            self.default_open = true;
+            "default_open"
+        } else if meta.path.is_ident("no_header") {
+            self.layout = Layout::NoHeader;
+            "no_header"
+        } else if meta.path.is_ident("inline") {
+            self.layout = Layout::Inline;
+            "inline"
+        } else if meta.path.is_ident("tabs") {
+            self.tabs = true;
+            "tabs"
+        } else if meta.path.is_ident("validate") {
+            set_once(&mut self.validate, &meta)?;
+            "validate"
+        } else if meta.path.is_ident("remote") {
+            if self.remote.is_some() {
+                return Err(meta.error("duplicate `remote` option"));
+            }
+            let lit: LitStr = meta.value()?.parse()?;
+            self.remote = Some(lit.parse()?);
+            "remote"
+        } else {
+            return Err(meta.error(format_args!(
+                "unknown container attribute `{}`",
+                meta.path.to_token_stream().to_string().replace(' ', "")
+            )));
+        };
+        if self.struct_options.iter().any(|(prev, _)| *prev == option) {
+            return Err(meta.error(format_args!("duplicate `{option}` option")));
+        }
+        self.struct_options.push((option, span));
+        Ok(())
+    }
+    /// Reports options that don't apply to the type, or don't make sense together
+    fn check(&self, data: &Data) -> syn::Result<()> {
+        // Unions are shown in a header, but support none of the other struct options
+        let unsupported = match data {
+            Data::Struct(_) => None,
+            Data::Enum(_) => self.struct_options.first(),
+            Data::Union(_) => self
+                .struct_options
+                .iter()
+                .find(|(option, _)| !matches!(*option, "title" | "default_open")),
+        };
+        if let Some((option, span)) = unsupported {
+            return Err(syn::Error::new(
+                *span,
+                format!("`{option}` is only supported on structs"),
+            ));
+        }
+        let layout_option = self
+            .struct_options
+            .iter()
+            .filter(|(option, _)| matches!(*option, "no_header" | "inline"))
+            .collect::<Vec<_>>();
+        if let [_, (option, span), ..] = layout_option[..] {
+            return Err(syn::Error::new(
+                *span,
+                format!(
+                    "`{option}` can't be used together with `{}`",
+                    layout_option[0].0
+                ),
+            ));
+        }
+        if let (Some(_), Data::Struct(s)) = (&self.remote, data) {
+            check_remote(&self.struct_options, s)?;
+        }
+        if let Some((layout_option, _)) = layout_option.first() {
+            for (option, span) in &self.struct_options {
+                if matches!(*option, "title" | "default_open" | "summary") {
+                    return Err(syn::Error::new(
+                        *span,
+                        format!("`{option}` has no effect with `{layout_option}`, as there is no header"),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rejects the options that need `self`, which doesn't exist for remote derives
+fn check_remote(struct_options: &[(&'static str, Span)], s: &syn::DataStruct) -> syn::Result<()> {
+    let no_self = |option: &str, span| {
+        syn::Error::new(
+            span,
+            format!("`{option}` can't be used together with `remote`, as there is no `self`"),
+        )
+    };
+    for (option, span) in struct_options {
+        if matches!(*option, "validate" | "button" | "property") {
+            return Err(no_self(option, *span));
+        }
+    }
+    for f in &s.fields {
+        let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let options = [
+            ("on_change", &attrs.on_change),
+            ("visible_if", &attrs.visible_if),
+            ("enabled_if", &attrs.enabled_if),
+        ];
+        for (option, expr) in options {
+            if let Some(expr) = expr {
+                return Err(no_self(option, expr.span()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A value that is shown like a field, but accessed through a getter and an optional setter
+pub struct Property {
+    pub name: LitStr,
+    /// `fn(&Self) -> T`
+    pub get: Expr,
+    /// `fn(&mut Self, T)`. Without a setter, the property is read-only.
+    pub set: Option<Expr>,
+}
+
+impl Property {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let (mut name, mut get, mut set) = (None, None, None);
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                set_once(&mut name, &meta)
+            } else if meta.path.is_ident("get") {
+                set_once(&mut get, &meta)
+            } else if meta.path.is_ident("set") {
+                set_once(&mut set, &meta)
+            } else {
+                Err(meta.error(format_args!(
+                    "unknown property option `{}`",
+                    meta.path.to_token_stream().to_string().replace(' ', "")
+                )))
+            }
+        })?;
+        match (name, get) {
+            (Some(name), Some(get)) => Ok(Self { name, get, set }),
+            (None, _) => Err(meta.error("property requires a `name`")),
+            (_, None) => Err(meta.error("property requires a `get` function")),
+        }
+    }
+}
+
+/// Options of an enum variant, parsed from its attributes
+#[derive(Default)]
+pub struct VariantAttrs {
+    /// Label to show instead of the variant name
+    pub rename: Option<LitStr>,
+    /// Hover text to show instead of the doc comment
+    pub tooltip: Option<LitStr>,
+}
+
+impl VariantAttrs {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut variant_attrs = Self::default();
+        for attr in attrs {
+            if attr.path().is_ident("inspect") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        set_once(&mut variant_attrs.rename, &meta)
+                    } else if meta.path.is_ident("tooltip") {
+                        set_once(&mut variant_attrs.tooltip, &meta)
+                    } else {
+                        Err(meta.error(format_args!(
+                            "unknown variant attribute `{}`",
+                            meta.path.to_token_stream().to_string().replace(' ', "")
+                        )))
+                    }
+                })?;
+            }
+        }
+        Ok(variant_attrs)
+    }
+}
+
+/// Generates deprecation warnings for uses of the legacy `#[opaque]` and
+/// `#[inspect_with(...)]` attributes.
+pub fn legacy_attr_warnings(data: &Data) -> TokenStream {
+    let fields: Vec<&syn::Field> = match data {
+        Data::Struct(s) => s.fields.iter().collect(),
+        Data::Enum(e) => e.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(u) => u.fields.named.iter().collect(),
+    };
+    let mut ts = TokenStream::new();
+    for attr in fields.iter().flat_map(|f| &f.attrs) {
+        let note = if attr.path().is_ident("opaque") {
+            "use `#[inspect(opaque)]` instead"
+        } else if attr.path().is_ident("inspect_with") {
+            "use `#[inspect(with = ...)]` with a module of `inspect` and `inspect_mut` functions instead"
+        } else {
+            continue;
+        };
+        // Referring to a deprecated item is the only way to emit a warning from a derive
+        let name = attr.path().get_ident();
+        let usage = quote_spanned! {attr.span()=> let _ = #name; };
+        ts.extend(quote! {
+            const _: () = {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const #name: () = ();
+                #usage
+            };
+        });
+    }
+    ts
+}
+
+/// Numeric options of a field, passed through to `egui::DragValue`
+#[derive(Default)]
+pub struct NumOpts {
+    range: Option<Expr>,
+    speed: Option<Expr>,
+    step: Option<Expr>,
+    decimals: Option<Expr>,
+    prefix: Option<Expr>,
+    suffix: Option<Expr>,
+    /// Span of the first numeric option, used for reporting conflicts
+    span: Option<Span>,
+}
+
+impl NumOpts {
+    /// Parses a numeric option. Returns `false` if `meta` is not a numeric option.
+    fn parse_meta(&mut self, meta: &ParseNestedMeta) -> syn::Result<bool> {
+        let slot = if meta.path.is_ident("range") {
+            &mut self.range
+        } else if meta.path.is_ident("speed") {
+            &mut self.speed
+        } else if meta.path.is_ident("step") {
+            &mut self.step
+        } else if meta.path.is_ident("decimals") {
+            &mut self.decimals
+        } else if meta.path.is_ident("prefix") {
+            &mut self.prefix
+        } else if meta.path.is_ident("suffix") {
+            &mut self.suffix
+        } else {
+            return Ok(false);
+        };
+        set_once(slot, meta)?;
+        self.span.get_or_insert(meta.path.span());
+        Ok(true)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.span.is_none()
+    }
+    /// Builder method calls configuring a `DragValue`
+    fn drag_value_config(&self) -> TokenStream {
+        let mut ts = TokenStream::new();
+        if let Some(range) = &self.range {
+            ts.extend(quote! { .range(#range) });
+        }
+        if let Some(speed) = &self.speed {
+            ts.extend(quote! { .speed(#speed) });
+        }
+        if let Some(decimals) = &self.decimals {
+            ts.extend(quote! { .fixed_decimals(#decimals) });
+        }
+        if let Some(prefix) = &self.prefix {
+            ts.extend(quote! { .prefix(#prefix) });
+        }
+        if let Some(suffix) = &self.suffix {
+            ts.extend(quote! { .suffix(#suffix) });
+        }
+        ts
+    }
+    /// Generates the code for showing the field through a `DragValue`.
+    ///
+    /// `ctx` is the context of the field, whose options apply to the `DragValue`.
+    pub fn inspect_call(
+        &self,
+        place: &TokenStream,
+        ctx: &TokenStream,
+        mutable: bool,
+    ) -> TokenStream {
+        let config = self.drag_value_config();
+        if !mutable {
+            // Show a copy of the value in a disabled `DragValue`, so it's formatted the same way
+            return quote! {
+                ui.add_enabled(false, ::egui_inspect::drag_value(&mut { #place }, #ctx) #config);
+            };
+        }
+        // `DragValue` has no notion of a step, so the value is snapped after editing
+        let snap = self.step.as_ref().map(|step| {
+            quote! {
+                if re.changed() {
+                    let step = (#step) as f64;
+                    let current = ::egui_inspect::egui::emath::Numeric::to_f64(#place);
+                    #place = ::egui_inspect::egui::emath::Numeric::from_f64((current / step).round() * step);
+                }
+            }
+        });
+        quote! {
+            let re = ui.add(::egui_inspect::drag_value(&mut #place, #ctx) #config);
+            #snap
+            response |= ::egui_inspect::InspectResponse::from(&re);
+        }
+    }
+}
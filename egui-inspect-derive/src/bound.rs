@@ -0,0 +1,157 @@
+//! Inference of the `Inspect` bounds of the generated impl
+
+use {
+    crate::attrs::{ContainerAttrs, FieldAttrs, FieldInspectKind},
+    proc_macro2::TokenStream,
+    quote::quote,
+    std::collections::HashSet,
+    syn::{
+        parse_quote,
+        visit::{self, Visit},
+        Data, DeriveInput, Generics, Ident, Type, TypePath, WherePredicate,
+    },
+};
+
+/// Returns the generics of `input`, with the bounds required by the generated impl added.
+///
+/// Unless overridden with `#[inspect(bound = "...")]`, every type parameter used in an
+/// auto-inspected field is required to implement `Inspect`, like serde does.
+/// The types of flattened fields are required to implement `InspectFields`,
+/// and the types of fields with a `display` option `InspectEnum`.
+/// The types of fields with numeric options additionally need to implement
+/// `egui::emath::Numeric`. Field types are only bounded if they refer to type parameters.
+/// Unions always require `Self: InspectUnion`.
+pub fn with_bounds(input: &DeriveInput, container: &ContainerAttrs) -> syn::Result<Generics> {
+    let mut generics = input.generics.clone();
+    // Reading the bytes and members of a union is only sound if the user vouches for it
+    if let Data::Union(_) = &input.data {
+        generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote! { Self: ::egui_inspect::InspectUnion });
+    }
+    if let Some(bound) = &container.bound {
+        generics
+            .make_where_clause()
+            .predicates
+            .extend(bound.iter().cloned());
+        return Ok(generics);
+    }
+    let fields: Vec<&syn::Field> = match &input.data {
+        Data::Struct(s) => s.fields.iter().collect(),
+        Data::Enum(e) => e.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(u) => u.fields.named.iter().collect(),
+    };
+    let params: HashSet<&Ident> = generics.type_params().map(|param| &param.ident).collect();
+    let mut inspect_visitor = TypeParamVisitor::new(&params);
+    let mut predicates = Vec::new();
+    for f in fields {
+        let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        if let Some(bound) = attrs.bound {
+            predicates.extend(bound);
+            continue;
+        }
+        match attrs.kind {
+            FieldInspectKind::Auto => {
+                inspect_visitor.visit_type(&f.ty);
+                // Numeric options edit the field itself through an `egui::DragValue`
+                if !attrs.num.is_empty() {
+                    predicates.extend(field_type_bound(
+                        &params,
+                        &f.ty,
+                        quote! { ::egui_inspect::egui::emath::Numeric },
+                    ));
+                }
+            }
+            // The field types implement these traits through their own bounds
+            FieldInspectKind::Enum(_) => predicates.extend(field_type_bound(
+                &params,
+                &f.ty,
+                quote! { ::egui_inspect::InspectEnum },
+            )),
+            FieldInspectKind::Flatten => predicates.extend(field_type_bound(
+                &params,
+                &f.ty,
+                quote! { ::egui_inspect::InspectFields },
+            )),
+            _ => {}
+        }
+    }
+    predicates.extend(inspect_visitor.predicates(&generics, quote! { ::egui_inspect::Inspect }));
+    generics.make_where_clause().predicates.extend(predicates);
+    Ok(generics)
+}
+
+/// A bound requiring the type of a field to implement `trait_`, if it refers to type parameters
+fn field_type_bound(
+    params: &HashSet<&Ident>,
+    ty: &Type,
+    trait_: TokenStream,
+) -> Option<WherePredicate> {
+    let mut visitor = TypeParamVisitor::new(params);
+    visitor.visit_type(ty);
+    visitor.uses_params().then(|| parse_quote! { #ty: #trait_ })
+}
+
+/// Collects the type parameters that a field type refers to
+struct TypeParamVisitor<'a> {
+    params: &'a HashSet<&'a Ident>,
+    used: HashSet<Ident>,
+    /// Associated types of type parameters, like `T::Item`
+    assoc_types: Vec<TypePath>,
+}
+
+impl<'a> TypeParamVisitor<'a> {
+    fn new(params: &'a HashSet<&'a Ident>) -> Self {
+        Self {
+            params,
+            used: HashSet::new(),
+            assoc_types: Vec::new(),
+        }
+    }
+    /// Whether the visited types refer to any type parameter
+    fn uses_params(&self) -> bool {
+        !self.used.is_empty() || !self.assoc_types.is_empty()
+    }
+    /// Bounds requiring the visited type parameters to implement `trait_`
+    fn predicates(self, generics: &Generics, trait_: TokenStream) -> Vec<WherePredicate> {
+        let mut predicates = Vec::new();
+        // Keep the bounds in declaration order, so the generated code is deterministic
+        for param in generics.type_params() {
+            if self.used.contains(&param.ident) {
+                let ident = &param.ident;
+                predicates.push(parse_quote! { #ident: #trait_ });
+            }
+        }
+        for ty in self.assoc_types {
+            predicates.push(parse_quote! { #ty: #trait_ });
+        }
+        predicates
+    }
+}
+
+impl<'ast> Visit<'ast> for TypeParamVisitor<'_> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        if ty.qself.is_none() {
+            if let Some(first) = ty.path.segments.first() {
+                if self.params.contains(&first.ident) {
+                    if ty.path.segments.len() == 1 {
+                        self.used.insert(first.ident.clone());
+                    } else {
+                        self.assoc_types.push(ty.clone());
+                    }
+                }
+            }
+        }
+        // `PhantomData<T>` implements `Inspect` for any `T`
+        if ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|seg| seg.ident == "PhantomData")
+        {
+            return;
+        }
+        visit::visit_type_path(self, ty);
+    }
+}
@@ -1,146 +1,1447 @@
+mod attrs;
+mod bound;
+
 use {
+    attrs::{
+        ContainerAttrs, EnumDisplay, FieldAttrs, FieldInspectKind, Layout, Property, VariantAttrs,
+    },
     proc_macro::TokenStream,
     quote::quote,
-    syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Lit, Member, Meta},
+    syn::{
+        ext::IdentExt, parse_macro_input, Attribute, Data, DeriveInput, Expr, Lit, Member, Meta,
+    },
 };
 
-enum FieldInspectKind {
-    /// Auto-inspected (it is assumed that the field implements Inspect)
-    Auto,
-    /// A function named by the token stream is called to inspect the field.
-    /// The function takes (thing: &mut T, ui: &mut Ui, id_source: u64)
-    WithFn(syn::Ident),
-    /// Not visited, left alone.
-    /// Useful when you want to skip a field that doesn't implement Inspect.
-    Opaque,
-}
+/// The doc comment of a field or variant
+fn doc_comment_string(attrs: &[Attribute]) -> String {
+    let mut out = String::new();
 
-fn inspect_kind(attrs: &[Attribute]) -> FieldInspectKind {
     for attr in attrs {
-        if attr.path().is_ident("opaque") {
-            return FieldInspectKind::Opaque;
-        } else if attr.path().is_ident("inspect_with") {
-            let fun: syn::Ident = attr.parse_args().expect("Failed to parse ident");
-            return FieldInspectKind::WithFn(fun);
+        if attr.path().is_ident("doc") {
+            if let Meta::NameValue(meta_name_value) = &attr.meta {
+                if let Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(s), ..
+                }) = &meta_name_value.value
+                {
+                    out.push_str(&s.value());
+                    out.push('\n');
+                }
+            }
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+fn member(i: usize, f: &syn::Field) -> Member {
+    match &f.ident {
+        Some(ident) => Member::from(ident.clone()),
+        None => Member::from(i),
+    }
+}
+
+/// The name of a field, as shown in the inspector
+fn field_name(i: usize, f: &syn::Field) -> String {
+    match &f.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => i.to_string(),
+    }
+}
+
+/// The `#[cfg(...)]` attributes of a field, which need to be forwarded to
+/// any generated code that refers to the field.
+fn cfg_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect()
+}
+
+/// Generates the statements that show every field of a struct or enum variant.
+///
+/// `places` contains a place expression for each field, through which the field is accessed.
+/// `properties` are shown after the fields, and are only supported for structs.
+/// `scope` distinguishes the local variables generated for different enum variants.
+/// The generated code expects `prefix: &str` in scope, which is prepended to the field labels,
+/// and `_ctx: &InspectCtx`, from which the contexts of the fields are derived.
+/// If `mutable` is true, the fields are inspected with `Inspect::inspect_mut`,
+/// otherwise with `Inspect::inspect`.
+fn field_exprs(
+    fields: &syn::Fields,
+    places: &[proc_macro2::TokenStream],
+    properties: &[Property],
+    mutable: bool,
+    tabs: bool,
+    scope: &str,
+) -> syn::Result<FieldsCode> {
+    let mut prelude = Vec::new();
+    let mut postlude = Vec::new();
+    let mut checks = Vec::new();
+    let mut errors = Vec::new();
+    let mut entries = Vec::new();
+    for (i, (f, place)) in fields.iter().zip(places).enumerate() {
+        let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let cfgs = cfg_attrs(&f.attrs);
+        let hover_text = match &attrs.tooltip {
+            Some(tooltip) => tooltip.value(),
+            None => doc_comment_string(&f.attrs),
+        };
+        // Read-only fields are shown with `Inspect::inspect`, even when inspecting mutably
+        let mutable = mutable && !attrs.read_only;
+        let name = match &attrs.rename {
+            Some(rename) => rename.value(),
+            None => field_name(i, f),
+        };
+        let opaque_label = format!("{name} <opaque>");
+        // The context is derived from the field itself, so it stays the same when the label is renamed
+        let key = field_name(i, f);
+        let ctx = quote! { &_ctx.child(#key) };
+        // Skipped fields are validated as well, their errors only show up in the summary
+        let invalid = attrs.validate.as_ref().map(|validate| {
+            let invalid = quote::format_ident!("__invalid{}_{}", scope, i);
+            checks.push(quote! {
+                #(#cfgs)*
+                let #invalid = ::core::result::Result::err((#validate)(&#place));
+            });
+            errors.push(quote! {
+                #(#cfgs)*
+                {
+                    if let ::core::option::Option::Some(error) = &#invalid {
+                        errors.push(format!("{}{}: {}", prefix, #name, error));
+                    }
+                }
+            });
+            invalid
+        });
+        let flatten = matches!(attrs.kind, FieldInspectKind::Flatten);
+        let display = match attrs.kind {
+            FieldInspectKind::Enum(display) => Some(display),
+            _ => None,
+        };
+        let expr = match attrs.kind {
+            FieldInspectKind::Auto | FieldInspectKind::Enum(_) => {
+                let inspect_call = if !attrs.num.is_empty() {
+                    attrs.num.inspect_call(place, &ctx, mutable)
+                } else if let (Some(display), true) = (display, mutable) {
+                    quote! { response |= ::egui_inspect::InspectEnum::inspect_enum_mut(&mut #place, ui, #ctx, #display); }
+                } else if mutable {
+                    quote! { response |= ::egui_inspect::Inspect::inspect_mut(&mut #place, ui, #ctx); }
+                } else {
+                    quote! { ::egui_inspect::Inspect::inspect(&#place, ui, #ctx) }
+                };
+                quote! {
+                    ui.horizontal(|ui| {
+                        let mut re = ui.add(::egui_inspect::egui::Label::new(format!("{}{}", prefix, #name)).sense(::egui_inspect::egui::Sense::click()));
+                        if !#hover_text.is_empty()  {
+                            re = re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                        }
+                        if re.clicked() {
+                            ::egui_inspect::copy_debug_text(ui, &#place);
+                        }
+                        #inspect_call
+                    });
+                }
+            }
+            FieldInspectKind::Opaque => {
+                quote! {
+                    ui.horizontal(|ui| {
+                        let re = ui.label(format!("{}{}", prefix, #opaque_label));
+                        if !#hover_text.is_empty()  {
+                            re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                        }
+                    });
+                }
+            }
+            FieldInspectKind::With(module) => {
+                let inspect_call = if mutable {
+                    quote! { response |= ::egui_inspect::InspectResponse::from(#module::inspect_mut(&mut #place, ui, #ctx)); }
+                } else {
+                    quote! { #module::inspect(&#place, ui, #ctx); }
+                };
+                quote! {
+                    ui.horizontal(|ui| {
+                        let re = ui.label(format!("{}{}", prefix, #name));
+                        if !#hover_text.is_empty()  {
+                            re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                        }
+                        #inspect_call
+                    });
+                }
+            }
+            FieldInspectKind::WithFn(fun) => {
+                // The custom function requires mutable access, so there is nothing
+                // we can show when inspecting immutably.
+                if mutable {
+                    quote! {
+                        ui.horizontal(|ui| {
+                            let re = ui.label(format!("{}{}", prefix, #name));
+                            if !#hover_text.is_empty()  {
+                                re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                            }
+                            response |= ::egui_inspect::InspectResponse::from(#fun(&mut #place, ui, #ctx));
+                        });
+                    }
+                } else {
+                    quote! {
+                        ui.horizontal(|ui| {
+                            let re = ui.label(format!("{}{}", prefix, #opaque_label));
+                            if !#hover_text.is_empty()  {
+                                re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                            }
+                        });
+                    }
+                }
+            }
+            FieldInspectKind::Flatten => {
+                let prefix = format!("{name}.");
+                if mutable {
+                    quote! {
+                        response |= ::egui_inspect::InspectFields::inspect_fields_mut(&mut #place, ui, &format!("{}{}", prefix, #prefix), #ctx);
+                    }
+                } else {
+                    quote! {
+                        ::egui_inspect::InspectFields::inspect_fields(&#place, ui, &format!("{}{}", prefix, #prefix), #ctx);
+                    }
+                }
+            }
+            FieldInspectKind::Skip => continue,
+        };
+        let mut expr = expr;
+        // Invalid fields get a red frame, with the error next to them.
+        // Flattened fields take up multiple lines, so the error goes below them instead.
+        if let Some(invalid) = &invalid {
+            let framed = quote! {
+                let (stroke, margin) = match &#invalid {
+                    ::core::option::Option::Some(_) => (::egui_inspect::egui::Stroke::new(1.0f32, ui.visuals().error_fg_color), 2.0f32),
+                    ::core::option::Option::None => (::egui_inspect::egui::Stroke::NONE, 0.0f32),
+                };
+                ::egui_inspect::egui::Frame::none().stroke(stroke).inner_margin(margin).show(ui, |ui| {
+                    #expr
+                });
+                if let ::core::option::Option::Some(error) = &#invalid {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            };
+            expr = if flatten {
+                framed
+            } else {
+                quote! {
+                    ui.horizontal(|ui| {
+                        #framed
+                    });
+                }
+            };
+        }
+        // The callback is called in the postlude, because `self` might still be borrowed
+        // right after the field is shown.
+        if let (Some(on_change), true) = (&attrs.on_change, mutable) {
+            let changed = quote::format_ident!("__changed{}_{}", scope, i);
+            prelude.push(quote! {
+                #(#cfgs)*
+                let mut #changed = false;
+            });
+            // The response is taken out, so only the changes of this field are looked at
+            expr = quote! {
+                let outer = ::core::mem::take(&mut response);
+                #expr
+                if response.changed() {
+                    #changed = true;
+                }
+                response |= outer;
+            };
+            postlude.push(quote! {
+                #(#cfgs)*
+                {
+                    if #changed {
+                        (#on_change)(self);
+                    }
+                }
+            });
         }
+        // The conditions are evaluated in the prelude, because `self` might already be
+        // borrowed by the time the field is shown.
+        if let Some(enabled_if) = &attrs.enabled_if {
+            let enabled = quote::format_ident!("__enabled{}_{}", scope, i);
+            prelude.push(quote! {
+                #(#cfgs)*
+                let #enabled: bool = #enabled_if;
+            });
+            expr = quote! {
+                ui.add_enabled_ui(#enabled, |ui| {
+                    #expr
+                });
+            };
+        }
+        if let Some(visible_if) = &attrs.visible_if {
+            let visible = quote::format_ident!("__visible{}_{}", scope, i);
+            prelude.push(quote! {
+                #(#cfgs)*
+                let #visible: bool = #visible_if;
+            });
+            expr = quote! {
+                if #visible {
+                    #expr
+                }
+            };
+        }
+        entries.push(FieldEntry {
+            order: attrs.order.unwrap_or(0),
+            group: attrs.group.map(|group| group.value()),
+            expr: quote! {
+                #(#cfgs)*
+                {
+                    #expr
+                }
+            },
+        });
+    }
+    for prop in properties {
+        entries.push(FieldEntry {
+            order: 0,
+            group: None,
+            expr: property_expr(prop, mutable),
+        });
     }
-    FieldInspectKind::Auto
+    Ok(FieldsCode {
+        prelude,
+        checks,
+        errors,
+        exprs: arrange_fields(entries, tabs),
+        postlude,
+    })
 }
 
-trait SynFieldExt {
-    fn doc_comment_string(&self) -> String;
+/// Generates the code for showing a property, through the `Inspect` impl of its value.
+///
+/// The context of the property is derived from its name.
+fn property_expr(prop: &Property, mutable: bool) -> proc_macro2::TokenStream {
+    let Property { name, get, set } = prop;
+    let ctx = quote! { &_ctx.child(#name) };
+    let inspect_call = match (set, mutable) {
+        // The setter is only called when the value changed
+        (Some(set), true) => quote! {
+            let mut value = (#get)(&*self);
+            let re = ::egui_inspect::Inspect::inspect_mut(&mut value, ui, #ctx);
+            if re.changed() {
+                (#set)(self, value);
+            }
+            response |= re;
+        },
+        _ => quote! {
+            ::egui_inspect::Inspect::inspect(&(#get)(&*self), ui, #ctx);
+        },
+    };
+    quote! {
+        ui.horizontal(|ui| {
+            if ui.add(::egui_inspect::egui::Label::new(format!("{}{}", prefix, #name)).sense(::egui_inspect::egui::Sense::click())).clicked() {
+                ::egui_inspect::copy_debug_text(ui, &(#get)(&*self));
+            }
+            #inspect_call
+        });
+    }
+}
+
+/// The generated code for showing the fields of a struct or enum variant
+struct FieldsCode {
+    /// Statements that have to run before `self` is borrowed for showing the fields
+    prelude: Vec<proc_macro2::TokenStream>,
+    /// Statements that run the field validators, where the fields are accessible
+    checks: Vec<proc_macro2::TokenStream>,
+    /// Statements that collect the validation errors into `errors: Vec<String>`
+    errors: Vec<proc_macro2::TokenStream>,
+    /// Statements that show the fields
+    exprs: Vec<proc_macro2::TokenStream>,
+    /// Statements that have to run after `self` is no longer borrowed for showing the fields
+    postlude: Vec<proc_macro2::TokenStream>,
+}
+
+/// The generated code for showing a field, along with where it should be shown
+struct FieldEntry {
+    order: i32,
+    group: Option<String>,
+    expr: proc_macro2::TokenStream,
 }
 
-impl SynFieldExt for syn::Field {
-    fn doc_comment_string(&self) -> String {
-        let mut out = String::new();
+/// Orders the fields, and puts the grouped fields under sub-headers, or tabs if `tabs` is true.
+///
+/// The fields are sorted by their `order`, keeping declaration order for equal ones.
+/// Ungrouped fields come first, followed by the groups in the order of their first field.
+fn arrange_fields(mut entries: Vec<FieldEntry>, tabs: bool) -> Vec<proc_macro2::TokenStream> {
+    entries.sort_by_key(|entry| entry.order);
+    let mut exprs = Vec::new();
+    let mut groups: Vec<(String, Vec<proc_macro2::TokenStream>)> = Vec::new();
+    for entry in entries {
+        match entry.group {
+            None => exprs.push(entry.expr),
+            Some(group) => match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, group_exprs)) => group_exprs.push(entry.expr),
+                None => groups.push((group, vec![entry.expr])),
+            },
+        }
+    }
+    if groups.is_empty() {
+        return exprs;
+    }
+    if tabs {
+        let names = groups.iter().map(|(name, _)| name);
+        let indices: Vec<_> = (0..groups.len()).collect();
+        let group_exprs = groups.iter().map(|(_, exprs)| exprs);
+        exprs.push(quote! {
+            let tab_id = ui.make_persistent_id(_ctx.id().with("egui_inspect_tab"));
+            let mut tab: usize = ui.data_mut(|data| data.get_temp(tab_id).unwrap_or(0));
+            ui.horizontal(|ui| {
+                #(ui.selectable_value(&mut tab, #indices, #names);)*
+            });
+            ui.data_mut(|data| data.insert_temp(tab_id, tab));
+            ui.separator();
+            match tab {
+                #(#indices => { #(#group_exprs)* })*
+                _ => {}
+            }
+        });
+    } else {
+        for (name, group_exprs) in groups {
+            exprs.push(quote! {
+                ::egui_inspect::egui::CollapsingHeader::new(#name).id_salt(_ctx.id().with(#name)).default_open(_ctx.options().default_open).show(ui, |ui| {
+                    #(#group_exprs)*
+                });
+            });
+        }
+    }
+    exprs
+}
 
-        for attr in &self.attrs {
-            if attr.path().is_ident("doc") {
-                if let Meta::NameValue(meta_name_value) = &attr.meta {
-                    if let Expr::Lit(syn::ExprLit {
-                        lit: Lit::Str(s), ..
-                    }) = &meta_name_value.value
-                    {
-                        out.push_str(&s.value());
-                        out.push('\n');
+/// Generates the code that formats the `summary` of a struct.
+///
+/// The placeholders of the format string name fields, like `{name}` or `{hp:.1}`,
+/// which are accessed through `receiver`.
+fn summary_format(
+    summary: &syn::LitStr,
+    fields: &syn::Fields,
+    receiver: &proc_macro2::TokenStream,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let value = summary.value();
+    let mut format = String::new();
+    let mut args = Vec::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                format.extend([c, c]);
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                summary,
+                                "unmatched `{` in summary",
+                            ))
+                        }
                     }
                 }
+                let (name, spec) = match placeholder.split_once(':') {
+                    Some((name, spec)) => (name.trim(), format!(":{spec}")),
+                    None => (placeholder.trim(), String::new()),
+                };
+                let Some((i, f)) = fields
+                    .iter()
+                    .enumerate()
+                    .find(|(i, f)| field_name(*i, f) == name)
+                else {
+                    return Err(syn::Error::new_spanned(
+                        summary,
+                        format!("summary refers to unknown field `{name}`"),
+                    ));
+                };
+                // The arguments are passed positionally, so tuple fields work too
+                format.push_str(&format!("{{{}{spec}}}", args.len()));
+                let memb = member(i, f);
+                args.push(quote! { &#receiver.#memb });
             }
+            '}' => return Err(syn::Error::new_spanned(summary, "unmatched `}` in summary")),
+            _ => format.push(c),
         }
+    }
+    Ok(quote! { format!(#format, #(#args),*) })
+}
 
-        out.trim_end().to_string()
+/// Generates the code that shows the validation errors of a struct above its fields.
+///
+/// `errors` are the statements collecting the errors of the fields,
+/// and `validate` is the validator of the struct as a whole.
+fn validation_summary(
+    errors: &[proc_macro2::TokenStream],
+    validate: Option<&Expr>,
+) -> proc_macro2::TokenStream {
+    if errors.is_empty() && validate.is_none() {
+        return proc_macro2::TokenStream::new();
     }
+    let container_error = validate.map(|validate| {
+        quote! {
+            if let ::core::result::Result::Err(error) = (#validate)(&*self) {
+                errors.push(error);
+            }
+        }
+    });
+    quote! {
+        let mut errors: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+        #(#errors)*
+        #container_error
+        if !errors.is_empty() {
+            let color = ui.visuals().error_fg_color;
+            ::egui_inspect::egui::Frame::group(ui.style()).stroke(::egui_inspect::egui::Stroke::new(1.0f32, color)).show(ui, |ui| {
+                for error in &errors {
+                    ui.colored_label(color, error);
+                }
+            });
+        }
+    }
+}
+
+/// Generates the match arms that show the fields of the active variant.
+///
+/// The fields are bound by reference, and accessed through the bindings.
+/// The prelude of the returned code has to run before the match, and the postlude after it.
+fn variant_field_arms(e: &syn::DataEnum, mutable: bool) -> syn::Result<FieldsCode> {
+    let mut prelude = Vec::new();
+    let mut postlude = Vec::new();
+    let mut arms = Vec::new();
+    for var in &e.variants {
+        if var.fields.is_empty() {
+            continue;
+        }
+        let name = &var.ident;
+        let membs: Vec<_> = var
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| member(i, f))
+            .collect();
+        let bindings: Vec<_> = (0..var.fields.len())
+            .map(|i| quote::format_ident!("__field_{}", i))
+            .collect();
+        let cfgs: Vec<_> = var.fields.iter().map(|f| cfg_attrs(&f.attrs)).collect();
+        let places: Vec<_> = bindings.iter().map(|b| quote! { (*#b) }).collect();
+        // The errors of the fields are only shown next to them, there is no summary for enums
+        let FieldsCode {
+            prelude: var_prelude,
+            checks,
+            exprs,
+            postlude: var_postlude,
+            ..
+        } = field_exprs(
+            &var.fields,
+            &places,
+            &[],
+            mutable,
+            false,
+            &name.unraw().to_string(),
+        )?;
+        prelude.extend(var_prelude);
+        postlude.extend(var_postlude);
+        arms.push(quote! {
+            Self::#name { #(#(#cfgs)* #membs: #bindings),* } => {
+                let prefix = "";
+                let _ctx = &ctx.salted(stringify!(#name));
+                #(#checks)*
+                #(#exprs)*
+            }
+        });
+    }
+    Ok(FieldsCode {
+        prelude,
+        checks: Vec::new(),
+        errors: Vec::new(),
+        exprs: arms,
+        postlude,
+    })
 }
 
-#[proc_macro_derive(Inspect, attributes(opaque, inspect_with))]
+/// Generates the code that shows a union.
+///
+/// The bytes of the union are always shown. The active member is shown if there is an
+/// `active_member` function, and every member if `unsafe_view_all_members` is given.
+fn union_body(
+    u: &syn::DataUnion,
+    container: &ContainerAttrs,
+    mutable: bool,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut body = proc_macro2::TokenStream::new();
+    // Only the active member can be accessed mutably
+    if let Some(active_member) = &container.active_member {
+        let mut arms = Vec::new();
+        for (i, f) in u.fields.named.iter().enumerate() {
+            let memb = member(i, f);
+            let name = field_name(i, f);
+            let place = if mutable {
+                quote! { (*unsafe { &mut self.#memb }) }
+            } else {
+                quote! { (*unsafe { &self.#memb }) }
+            };
+            // The member is shown like it was the only field of a struct
+            let fields = syn::Fields::Named(syn::FieldsNamed {
+                brace_token: u.fields.brace_token,
+                named: std::iter::once(f.clone()).collect(),
+            });
+            let FieldsCode {
+                prelude,
+                checks,
+                exprs,
+                postlude,
+                ..
+            } = field_exprs(&fields, &[place], &[], mutable, false, &name)?;
+            let cfgs = cfg_attrs(&f.attrs);
+            arms.push(quote! {
+                #(#cfgs)*
+                #name => {
+                    #(#checks)*
+                    #(#prelude)*
+                    #(#exprs)*
+                    #(#postlude)*
+                }
+            });
+        }
+        body.extend(quote! {
+            match (#active_member)(&*self) {
+                #(#arms)*
+                name => {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("unknown active member `{}`", name));
+                }
+            }
+        });
+    }
+    if container.unsafe_view_all_members {
+        let places: Vec<_> = u
+            .fields
+            .named
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let memb = member(i, f);
+                quote! { (*unsafe { &self.#memb }) }
+            })
+            .collect();
+        let FieldsCode {
+            prelude,
+            checks,
+            exprs,
+            postlude,
+            ..
+        } = field_exprs(
+            &syn::Fields::Named(u.fields.clone()),
+            &places,
+            &[],
+            false,
+            false,
+            "",
+        )?;
+        body.extend(quote! {
+            // The members get their own ids, so they don't share state with the active member
+            let _ctx = &ctx.salted("All members");
+            ::egui_inspect::egui::CollapsingHeader::new("All members").id_salt(_ctx.id()).show(ui, |ui| {
+                #(#checks)*
+                #(#prelude)*
+                #(#exprs)*
+                #(#postlude)*
+            });
+        });
+    }
+    body.extend(quote! {
+        // `InspectUnion` guarantees that all bytes of the union are initialized
+        let bytes = unsafe {
+            ::core::slice::from_raw_parts(
+                (&*self as *const Self).cast::<u8>(),
+                ::core::mem::size_of::<Self>(),
+            )
+        };
+        let hex = bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<::std::vec::Vec<_>>().join(" ");
+        ui.horizontal_wrapped(|ui| {
+            ui.label("bytes");
+            ui.monospace(hex);
+        });
+    });
+    Ok(body)
+}
+
+/// Expressions telling whether a field can be constructed, and constructing it.
+///
+/// Fields inspected through `Inspect` are constructed with `Inspect::default_value`,
+/// which also works for type parameters, thanks to the inferred `Inspect` bounds.
+/// Otherwise, the field is constructed through `Default`, which is only detected for
+/// concrete types.
+fn field_default(
+    f: &syn::Field,
+) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let ty = &f.ty;
+    let probe = quote! { (&&::egui_inspect::DefaultProbe::<#ty>(::core::marker::PhantomData)) };
+    let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+    Ok(match attrs.kind {
+        FieldInspectKind::Auto | FieldInspectKind::Enum(_) => (
+            quote! { (<#ty as ::egui_inspect::Inspect>::has_default_value() || #probe.has_default()) },
+            quote! { <#ty as ::egui_inspect::Inspect>::default_value().or_else(|| #probe.maybe_default()) },
+        ),
+        _ => (
+            quote! { #probe.has_default() },
+            quote! { #probe.maybe_default() },
+        ),
+    })
+}
+
+/// Generates `Inspect::default_value` and `Inspect::has_default_value` of a generic type.
+///
+/// `Default` of a generic type can't be detected in the generic impl, so unless it's
+/// bounded, the value is built from the default values of its fields instead.
+/// Structs build all of their fields, and enums the variant marked with `#[default]`,
+/// like `#[derive(Default)]` does.
+/// Returns `None` for other types, which only construct values through `Default`.
+fn generic_default(
+    data: &Data,
+) -> syn::Result<Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)>> {
+    let (path, fields) = match data {
+        Data::Struct(s) => (quote! { Self }, &s.fields),
+        Data::Enum(e) => {
+            let default_var = e
+                .variants
+                .iter()
+                .find(|var| var.attrs.iter().any(|attr| attr.path().is_ident("default")));
+            match default_var {
+                Some(var) => {
+                    let name = &var.ident;
+                    (quote! { Self::#name }, &var.fields)
+                }
+                None => return Ok(None),
+            }
+        }
+        Data::Union(_) => return Ok(None),
+    };
+    let mut checks = Vec::new();
+    let mut inits = Vec::new();
+    for (i, f) in fields.iter().enumerate() {
+        let (has, value) = field_default(f)?;
+        let memb = member(i, f);
+        let cfgs = cfg_attrs(&f.attrs);
+        checks.push(quote! { #(#cfgs)* { has &= #has; } });
+        inits.push(quote! { #(#cfgs)* #memb: #value?, });
+    }
+    Ok(Some((
+        quote! {
+            let mut has = true;
+            #(#checks)*
+            has
+        },
+        quote! {
+            ::core::option::Option::Some(#path { #(#inits)* })
+        },
+    )))
+}
+
+/// Generates the code that writes the fields of a struct or enum variant to the formatter `f`,
+/// like `#[derive(Debug)]` would.
+///
+/// `places` are references to the fields.
+/// Fields that are inspected through `Inspect` are formatted with `Inspect::fmt_debug`.
+/// Other fields are formatted through `Debug`, which is only detected for concrete types,
+/// and written as `_` without it.
+fn debug_fields(
+    name: &str,
+    fields: &syn::Fields,
+    places: &[proc_macro2::TokenStream],
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut calls = Vec::new();
+    for ((i, f), place) in fields.iter().enumerate().zip(places) {
+        let ty = &f.ty;
+        let value = match FieldAttrs::from_attrs(&f.attrs)?.kind {
+            FieldInspectKind::Auto | FieldInspectKind::Enum(_) => {
+                quote! { &::egui_inspect::DebugText(#place) }
+            }
+            _ => quote! {
+                &::egui_inspect::DebugFn(|f: &mut ::core::fmt::Formatter<'_>| {
+                    use ::egui_inspect::{ProbeDebug as _, ProbeNoDebug as _};
+                    (&&::egui_inspect::DebugProbe::<#ty>(::core::marker::PhantomData))
+                        .maybe_fmt(#place, f)
+                        .unwrap_or_else(|| f.write_str("_"))
+                })
+            },
+        };
+        let cfgs = cfg_attrs(&f.attrs);
+        let call = match &f.ident {
+            Some(_) => {
+                let field_name = field_name(i, f);
+                quote! { builder.field(#field_name, #value); }
+            }
+            None => quote! { builder.field(#value); },
+        };
+        calls.push(quote! { #(#cfgs)* { #call } });
+    }
+    Ok(match fields {
+        syn::Fields::Named(_) => quote! {
+            let mut builder = f.debug_struct(#name);
+            #(#calls)*
+            builder.finish()
+        },
+        syn::Fields::Unnamed(_) => quote! {
+            let mut builder = f.debug_tuple(#name);
+            #(#calls)*
+            builder.finish()
+        },
+        syn::Fields::Unit => quote! { f.write_str(#name) },
+    })
+}
+
+/// Generates the body of `Inspect::fmt_debug` for types whose `Debug` impl can't be detected,
+/// like types that don't implement it, or generic types.
+///
+/// The value is formatted from its fields, like `#[derive(Debug)]` would.
+/// Unions have no textual representation without `Debug`.
+fn debug_from_fields(data: &Data, ty_ident: &syn::Ident) -> syn::Result<proc_macro2::TokenStream> {
+    let body = match data {
+        Data::Struct(s) => {
+            let places: Vec<_> = s
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| {
+                    let memb = member(i, f);
+                    quote! { &self.#memb }
+                })
+                .collect();
+            debug_fields(&ty_ident.unraw().to_string(), &s.fields, &places)?
+        }
+        Data::Enum(e) if e.variants.is_empty() => return Ok(quote! { match *self {} }),
+        Data::Enum(e) => {
+            let mut arms = Vec::new();
+            for var in &e.variants {
+                let name = &var.ident;
+                let membs: Vec<_> = var
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| member(i, f))
+                    .collect();
+                let bindings: Vec<_> = (0..var.fields.len())
+                    .map(|i| quote::format_ident!("__field_{}", i))
+                    .collect();
+                let cfgs: Vec<_> = var.fields.iter().map(|f| cfg_attrs(&f.attrs)).collect();
+                let places: Vec<_> = bindings.iter().map(|b| quote! { #b }).collect();
+                let body = debug_fields(&name.unraw().to_string(), &var.fields, &places)?;
+                let var_cfgs = cfg_attrs(&var.attrs);
+                arms.push(quote! {
+                    #(#var_cfgs)*
+                    Self::#name { #(#(#cfgs)* #membs: #bindings),* } => { #body }
+                });
+            }
+            quote! { match self { #(#arms)* } }
+        }
+        Data::Union(_) => return Ok(quote! { ::core::option::Option::None }),
+    };
+    Ok(quote! { ::core::option::Option::Some({ #body }) })
+}
+
+/// Derives `Inspect` for a struct, enum or union.
+///
+/// For structs, `InspectFields` is derived as well.
+///
+/// Fields can be configured with `#[inspect(...)]` attributes:
+///
+/// - `rename = "..."`: Label to show instead of the field name
+/// - `tooltip = "..."`: Hover text to show instead of the doc comment
+/// - `read_only`: Inspect the field immutably, even when the struct is inspected mutably
+/// - `skip`: Don't show the field at all
+/// - `opaque`: Only show the name of the field. Useful for fields that don't implement `Inspect`.
+/// - `flatten`: Show the fields of the field inline, with labels prefixed by the field name.
+///   The type of the field must implement `InspectFields`, which is derived for structs.
+/// - `with = path::to::module`: Inspect the field with the `inspect` and `inspect_mut`
+///   functions of a module or type, like serde's `with`. `inspect` takes
+///   `(&T, &mut egui::Ui, &InspectCtx)`, and `inspect_mut` takes
+///   `(&mut T, &mut egui::Ui, &InspectCtx)` and returns `InspectResponse` or `()`.
+/// - `range = a..=b`, `speed = x`, `step = x`, `decimals = n`, `prefix = "..."`, `suffix = "..."`:
+///   Configure the `egui::DragValue` of a numeric field
+///
+/// - `bound = "..."`: Replace the `Inspect` bounds inferred from the type of this field
+/// - `visible_if = expr`: Only show the field if `expr` evaluates to true.
+///   `expr` is evaluated before any field is shown, and can refer to `self`.
+/// - `enabled_if = expr`: Only allow interacting with the field if `expr` evaluates to true.
+///   `expr` is evaluated before any field is shown, and can refer to `self`.
+/// - `on_change = path::to::fn`: Call `fn(&mut self)` after the field was changed in the inspector.
+///   Changes are detected through the `InspectResponse` of the field, so with `with` or the legacy
+///   `#[inspect_with]`, `fn` is only called if the custom function returns `InspectResponse`
+///   and marks it as changed. Functions returning `()` never trigger it.
+/// - `validate = path::to::fn`: Check the field with `fn(&T) -> Result<(), String>`.
+///   Invalid fields are shown with a red frame and the error next to them.
+/// - `group = "..."`: Show the field under a sub-header with the given name,
+///   along with the other fields of the same group. Ungrouped fields come first.
+/// - `order = n`: Show the field at a different position than its declaration.
+///   Fields are sorted by `order`, which defaults to 0.
+/// - `display = "combo" | "radio" | "toggle"`: Select the variant of an enum field with the
+///   given kind of widget, instead of the one configured on the enum.
+///   The type of the field must implement `InspectEnum`, which is derived for enums.
+///
+/// The struct or enum itself can be configured with `#[inspect(...)]` attributes:
+///
+/// - `bound = "..."`: Replace all inferred bounds of the generated impl.
+///   By default, every type parameter used in an auto-inspected field is required
+///   to implement `Inspect`. Fields with numeric options also require their type to implement
+///   `egui::emath::Numeric`.
+///
+/// For enums, `InspectEnum` is derived as well, and the following options are supported:
+///
+/// - `display = "combo" | "radio" | "toggle"`: How the variant is selected when inspecting
+///   mutably. Defaults to a combo box.
+/// - `show_discriminant`: Show the discriminant next to the name of each variant.
+///   Only supported on enums without fields.
+///
+/// Variants can be configured with `rename = "..."` and `tooltip = "..."`, like fields.
+/// The doc comments of the variants are shown as tooltips in the variant selector.
+///
+/// The following options are only supported on structs:
+///
+/// - `title = "..."`: Header text to show instead of the type name
+/// - `default_open`: Start out with the header expanded
+/// - `summary = "..."`: Header text to show while collapsed, formatted from the fields, like
+///   `"{name} ({hp} hp)"`. Format specs like `{x:.2}` or `{x:?}` are supported.
+///   Without a summary, the collapsed header shows the title followed by a short preview of
+///   the `Debug` representation.
+/// - `no_header`: Show the fields directly in the parent, without a collapsing header
+/// - `inline`: Show the fields directly in the parent, on a single line.
+///   Useful for small structs, like vectors.
+/// - `tabs`: Show the field groups as tabs instead of sub-headers
+/// - `validate = path::to::fn`: Check the struct with `fn(&Self) -> Result<(), String>`.
+///   The errors of the struct and its validated fields are summarized above the fields.
+/// - `property(name = "...", get = path::to::getter, set = path::to::setter)`:
+///   Show a value that isn't stored in a field, like a field.
+///   The getter is a `fn(&Self) -> T`, and the optional setter a `fn(&mut Self, T)`,
+///   where `T` implements `Inspect`. Without a setter, the property is read-only.
+///   The setter is only called when the `InspectResponse` of the value reports a change.
+/// - `button("...", path::to::fn)`: Show a button above the fields, which calls `fn(&mut self)`
+///   when clicked. Can be given multiple times. Only shown when inspecting mutably.
+/// - `remote = "path::to::Type"`: Derive for a foreign type, which the struct mirrors.
+///   Instead of implementing `Inspect`, `inspect` and `inspect_mut` functions are generated on
+///   the struct, which can be used with `#[inspect(with = Struct)]`.
+///   The fields of the struct have to match the fields of the foreign type.
+///   Options that need `self`, like `on_change` or `visible_if`, are not supported.
+///
+/// If the type implements `Default`, new values are created through it, like when adding an
+/// element to a `Vec` of the type. `Default` is only detected for generic types if the type
+/// parameters are bounded by `Default`. Otherwise, generic structs are built from the default
+/// values of their fields, and generic enums from the variant marked with `#[default]`.
+/// Switching an enum to a variant with fields requires default values for the fields,
+/// through `Inspect` like for adding them to a `Vec`, or through `Default`.
+///
+/// `Debug` is optional. If the type implements it, the derived `Inspect::fmt_debug` uses it
+/// for previews and for copying values to the clipboard, by clicking the label of a field.
+/// Otherwise, and for generic types whose `Debug` impl can't be detected, the value is
+/// formatted from its fields like `#[derive(Debug)]` would, through `Inspect::fmt_debug` of
+/// the fields. Fields that aren't inspected through `Inspect`, like `opaque` ones, are written
+/// as `_` if their `Debug` impl can't be detected either.
+///
+/// Unions are shown as their bytes, in hexadecimal. Deriving `Inspect` for a union
+/// requires an `unsafe impl InspectUnion` for it, which vouches that all of its bytes are
+/// always initialized, like for `#[repr(C)]` unions of plain old data without padding.
+/// See the safety requirements of `InspectUnion`. Unions support the following options:
+///
+/// - `title = "..."` and `default_open`: Like for structs
+/// - `active_member = path::to::fn`: Show the member that is in use, which is editable when
+///   inspecting mutably. `fn(&Self) -> &str` returns the name of the member.
+/// - `unsafe_view_all_members`: Show every member, reinterpreting the bytes of the union.
+///   All members need to be valid for any bit pattern.
+///
+/// The legacy `#[opaque]` attribute is a deprecated alias of `#[inspect(opaque)]`.
+/// The legacy `#[inspect_with(path::to::fn)]` attribute is deprecated as well. It inspects
+/// the field with a single function like `inspect_mut`, so the field is opaque when
+/// inspecting immutably.
+#[proc_macro_derive(Inspect, attributes(inspect, opaque, inspect_with))]
 pub fn derive_inspect(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let ty_ident = input.ident;
-    let ts = match input.data {
+    match expand(input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let ty_ident = &input.ident;
+    let container_attrs = ContainerAttrs::from_attrs(&input.attrs, &input.data)?;
+    let generics = bound::with_bounds(&input, &container_attrs)?;
+    let legacy_attr_warnings = attrs::legacy_attr_warnings(&input.data);
+    // The doc comment of the type is shown as a tooltip on its header, or its variant selector
+    let type_doc = doc_comment_string(&input.attrs);
+    let type_doc_tooltip = (!type_doc.is_empty()).then(|| {
+        quote! {
+            re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #type_doc));
+        }
+    });
+    let (intro_generics, forward_generics, where_clauses) = generics.split_for_impl();
+    let mut extra_impls = proc_macro2::TokenStream::new();
+    let (ts, ts_mut) = match &input.data {
         Data::Struct(s) => {
-            let mut exprs = Vec::new();
-            for (i, f) in s.fields.iter().enumerate() {
-                let memb = match &f.ident {
-                    Some(ident) => Member::from(ident.clone()),
-                    None => Member::from(i),
+            // Remote derives access the fields through the foreign value
+            let receiver = match &container_attrs.remote {
+                Some(_) => quote! { value },
+                None => quote! { self },
+            };
+            let places: Vec<_> = s
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| {
+                    let memb = member(i, f);
+                    quote! { #receiver.#memb }
+                })
+                .collect();
+            let tabs = container_attrs.tabs;
+            let FieldsCode {
+                prelude,
+                checks,
+                errors,
+                exprs,
+                postlude,
+            } = field_exprs(
+                &s.fields,
+                &places,
+                &container_attrs.properties,
+                false,
+                tabs,
+                "",
+            )?;
+            let FieldsCode {
+                prelude: prelude_mut,
+                exprs: exprs_mut,
+                postlude: postlude_mut,
+                ..
+            } = field_exprs(
+                &s.fields,
+                &places,
+                &container_attrs.properties,
+                true,
+                tabs,
+                "",
+            )?;
+            let summary = validation_summary(&errors, container_attrs.validate.as_ref());
+            let title = match (&container_attrs.title, &container_attrs.remote) {
+                (Some(title), _) => quote! { #title },
+                (None, Some(remote)) => {
+                    let name = remote
+                        .segments
+                        .last()
+                        .map(|seg| seg.ident.unraw().to_string());
+                    quote! { #name }
+                }
+                (None, None) => quote! { stringify!(#ty_ident) },
+            };
+            let default_open = container_attrs.default_open;
+            // While collapsed, the header shows a summary of the value instead of the title,
+            // or the title followed by a preview of the value
+            let collapsed = match (&container_attrs.summary, &container_attrs.remote) {
+                (Some(summary), _) => summary_format(summary, &s.fields, &receiver)?,
+                // The foreign type might not implement `Debug`
+                (None, Some(_)) => quote! { ::std::string::ToString::to_string(#title) },
+                (None, None) => quote! {
+                    match ::egui_inspect::debug_preview(self) {
+                        ::core::option::Option::Some(preview) => format!("{} {}", #title, preview),
+                        ::core::option::Option::None => ::std::string::ToString::to_string(#title),
+                    }
+                },
+            };
+            let show = |inspect_fields| match container_attrs.layout {
+                Layout::Header => quote! {
+                    let text = if ::egui_inspect::header_open(ui, ctx.id(), #default_open || ctx.options().default_open) {
+                        ::std::string::ToString::to_string(#title)
+                    } else {
+                        #collapsed
+                    };
+                    let re = ::egui_inspect::egui::CollapsingHeader::new(text).id_salt(ctx.id()).default_open(#default_open || ctx.options().default_open).show(ui, |ui| {
+                        #inspect_fields
+                    }).header_response;
+                    #type_doc_tooltip
+                },
+                // The parent might be laying out horizontally, so the layout needs to be explicit
+                Layout::NoHeader => quote! {
+                    ui.vertical(|ui| {
+                        #inspect_fields
+                    });
+                },
+                Layout::Inline => quote! {
+                    ui.horizontal(|ui| {
+                        #inspect_fields
+                    });
+                },
+            };
+            if let Some(remote) = &container_attrs.remote {
+                let remote_ty = match remote.segments.last() {
+                    Some(seg) if seg.arguments.is_empty() => quote! { #remote #forward_generics },
+                    _ => quote! { #remote },
                 };
-                let doc_comment_string = f.doc_comment_string();
-                match inspect_kind(&f.attrs) {
-                    FieldInspectKind::Auto => {
-                        let ident = &f.ident;
-                        exprs.push(quote! {
-                            ui.horizontal(|ui| {
-                                let mut re = ui.add(::egui_inspect::egui::Label::new(stringify!(#ident)).sense(::egui_inspect::egui::Sense::click()));
-                                if !#doc_comment_string.is_empty()  {
-                                    re = re.on_hover_text(#doc_comment_string);
-                                }
-                                if re.clicked() {
-                                    ui.output_mut(|o| o.copied_text = format!("{:?}", self.#memb));
-                                }
-                                ::egui_inspect::Inspect::inspect_mut(&mut self.#memb, ui, #i as u64)
-                            });
-                        });
+                let vis = &input.vis;
+                let membs: Vec<_> = s
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| member(i, f))
+                    .collect();
+                let tys: Vec<_> = s.fields.iter().map(|f| &f.ty).collect();
+                let cfgs: Vec<_> = s.fields.iter().map(|f| cfg_attrs(&f.attrs)).collect();
+                let inspect_fields = show(quote! {
+                    let prefix = "";
+                    let _ctx = ctx;
+                    #(#checks)*
+                    #summary
+                    #(#prelude)*
+                    #(#exprs)*
+                    #(#postlude)*
+                });
+                let inspect_fields_mut = show(quote! {
+                    let prefix = "";
+                    let _ctx = ctx;
+                    #(#checks)*
+                    #summary
+                    #(#prelude_mut)*
+                    #(#exprs_mut)*
+                    #(#postlude_mut)*
+                });
+                return Ok(quote! {
+                    #legacy_attr_warnings
+                    impl #intro_generics #ty_ident #forward_generics #where_clauses {
+                        /// Inspects the remote value immutably. Usable with `#[inspect(with = ...)]`
+                        /// on fields of the remote type, along with `inspect_mut`.
+                        #vis fn inspect(value: &#remote_ty, ui: &mut ::egui_inspect::egui::Ui, ctx: &::egui_inspect::InspectCtx) {
+                            #inspect_fields
+                        }
+                        /// Inspects the remote value mutably. Usable with `#[inspect(with = ...)]`
+                        /// on fields of the remote type, along with `inspect`.
+                        #vis fn inspect_mut(value: &mut #remote_ty, ui: &mut ::egui_inspect::egui::Ui, ctx: &::egui_inspect::InspectCtx) -> ::egui_inspect::InspectResponse {
+                            if ctx.read_only() {
+                                Self::inspect(value, ui, ctx);
+                                return ::egui_inspect::InspectResponse::default();
+                            }
+                            let mut response = ::egui_inspect::InspectResponse::default();
+                            #inspect_fields_mut
+                            response
+                        }
                     }
-                    FieldInspectKind::Opaque => {
-                        exprs.push(quote! {
-                            ui.horizontal(|ui| {
-                                let re = ui.label(concat!(stringify!(#memb), " <opaque>"));
-                                if !#doc_comment_string.is_empty()  {
-                                    re.on_hover_text(#doc_comment_string);
+                    // The mirror struct is never constructed, but its fields are used for
+                    // checking that they match the remote type.
+                    const _: () = {
+                        #[allow(dead_code)]
+                        fn check_fields #intro_generics (value: &#remote_ty, mirror: &#ty_ident #forward_generics) #where_clauses {
+                            #(
+                                #(#cfgs)*
+                                {
+                                    let _: &#tys = &value.#membs;
+                                    let _: &#tys = &mirror.#membs;
                                 }
-                            });
-                        });
+                            )*
+                        }
+                    };
+                });
+            }
+            extra_impls.extend(quote! {
+                impl #intro_generics ::egui_inspect::InspectFields for #ty_ident #forward_generics #where_clauses {
+                    fn inspect_fields(&self, ui: &mut ::egui_inspect::egui::Ui, prefix: &str, _ctx: &::egui_inspect::InspectCtx) {
+                        #(#checks)*
+                        #summary
+                        #(#prelude)*
+                        #(#exprs)*
+                        #(#postlude)*
                     }
-                    FieldInspectKind::WithFn(fun) => {
-                        exprs.push(quote! {
-                            ui.horizontal(|ui| {
-                                let re = ui.label(stringify!(#memb));
-                                if !#doc_comment_string.is_empty()  {
-                                    re.on_hover_text(#doc_comment_string);
-                                }
-                                #fun(&mut self.#memb, ui, #i as u64)
-                            });
+                    fn inspect_fields_mut(&mut self, ui: &mut ::egui_inspect::egui::Ui, prefix: &str, _ctx: &::egui_inspect::InspectCtx) -> ::egui_inspect::InspectResponse {
+                        if _ctx.read_only() {
+                            ::egui_inspect::InspectFields::inspect_fields(self, ui, prefix, _ctx);
+                            return ::egui_inspect::InspectResponse::default();
+                        }
+                        let mut response = ::egui_inspect::InspectResponse::default();
+                        #(#checks)*
+                        #summary
+                        #(#prelude_mut)*
+                        #(#exprs_mut)*
+                        #(#postlude_mut)*
+                        response
+                    }
+                }
+            });
+            // The buttons need mutable access, so they are only shown when inspecting mutably
+            let buttons = (!container_attrs.buttons.is_empty()).then(|| {
+                let (labels, funs): (Vec<_>, Vec<_>) =
+                    container_attrs.buttons.iter().cloned().unzip();
+                let buttons = quote! {
+                    #(
+                        if ui.button(#labels).clicked() {
+                            (#funs)(self);
+                            response.mark_changed();
+                        }
+                    )*
+                };
+                if container_attrs.layout == Layout::Inline {
+                    buttons
+                } else {
+                    quote! {
+                        ui.horizontal(|ui| {
+                            #buttons
                         });
                     }
                 }
-            }
-            quote! {
-                ::egui_inspect::egui::CollapsingHeader::new(stringify!(#ty_ident)).id_source(id_source).show(ui, |ui| {
-                    #(#exprs)*
-                });
-            }
+            });
+            let show_mut = show(quote! {
+                #buttons
+                response |= ::egui_inspect::InspectFields::inspect_fields_mut(self, ui, "", ctx);
+            });
+            (
+                show(quote! { ::egui_inspect::InspectFields::inspect_fields(self, ui, "", ctx); }),
+                quote! {
+                    let mut response = ::egui_inspect::InspectResponse::default();
+                    #show_mut
+                    response
+                },
+            )
+        }
+        // There is no value of an enum without variants, so there is nothing to show
+        Data::Enum(e) if e.variants.is_empty() => {
+            extra_impls.extend(quote! {
+                impl #intro_generics ::egui_inspect::InspectEnum for #ty_ident #forward_generics #where_clauses {
+                    fn inspect_enum_mut(&mut self, _ui: &mut ::egui_inspect::egui::Ui, _ctx: &::egui_inspect::InspectCtx, _display: ::egui_inspect::EnumDisplay) -> ::egui_inspect::InspectResponse {
+                        match *self {}
+                    }
+                }
+            });
+            (quote! { match *self {} }, quote! { match *self {} })
         }
         Data::Enum(e) => {
             let mut sel_name_match_exprs = Vec::new();
             let mut selectable_value_exprs = Vec::new();
             for var in &e.variants {
+                let var_attrs = VariantAttrs::from_attrs(&var.attrs)?;
                 let name = &var.ident;
-                sel_name_match_exprs.push(quote! {Self::#name => stringify!(#name)});
-                selectable_value_exprs
-                    .push(quote! {ui.selectable_value(self, Self::#name, stringify!(#name))});
-            }
-            quote! {
-                let sel_text = match self {
-                    #(#sel_name_match_exprs,)*
+                let name_str = match &var_attrs.rename {
+                    Some(rename) => rename.value(),
+                    None => name.unraw().to_string(),
+                };
+                let hover_text = match &var_attrs.tooltip {
+                    Some(tooltip) => tooltip.value(),
+                    None => doc_comment_string(&var.attrs),
                 };
-                ::egui_inspect::egui::ComboBox::new(id_source, stringify!(#ty_ident)).selected_text(sel_text).show_ui(ui, |ui| {
-                    #(#selectable_value_exprs;)*
+                let text = if container_attrs.show_discriminant {
+                    quote! { format!("{} ({})", #name_str, Self::#name as i128) }
+                } else {
+                    quote! { #name_str }
+                };
+                sel_name_match_exprs.push(quote! {Self::#name { .. } => #text});
+                // Switching to a variant builds its fields from their default values.
+                // If some field has none, the variant can't be selected.
+                let membs: Vec<_> = var
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| member(i, f))
+                    .collect();
+                let (has_defaults, defaults): (Vec<_>, Vec<_>) = var
+                    .fields
+                    .iter()
+                    .map(field_default)
+                    .collect::<syn::Result<Vec<_>>>()?
+                    .into_iter()
+                    .unzip();
+                let cfgs: Vec<_> = var.fields.iter().map(|f| cfg_attrs(&f.attrs)).collect();
+                selectable_value_exprs.push(quote! {
+                    use ::egui_inspect::{ProbeDefault as _, ProbeNoDefault as _};
+                    let selected = matches!(self, Self::#name { .. });
+                    let mut constructible = true;
+                    #(
+                        #(#cfgs)*
+                        {
+                            constructible &= #has_defaults;
+                        }
+                    )*
+                    let text = #text;
+                    let mut re = if radio {
+                        ui.add_enabled(constructible, ::egui_inspect::egui::RadioButton::new(selected, text))
+                    } else {
+                        ui.add_enabled(constructible, ::egui_inspect::egui::SelectableLabel::new(selected, text))
+                    };
+                    if !#hover_text.is_empty() {
+                        re = re.on_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text)).on_disabled_hover_ui(|ui| ::egui_inspect::show_doc(ui, #hover_text));
+                    }
+                    if re.clicked() && !selected {
+                        let new = (|| {
+                            ::core::option::Option::Some(Self::#name {
+                                #(#(#cfgs)* #membs: #defaults?,)*
+                            })
+                        })();
+                        if let ::core::option::Option::Some(new) = new {
+                            *self = new;
+                            response.mark_changed();
+                        }
+                    }
                 });
             }
+            let FieldsCode {
+                prelude,
+                exprs: arms,
+                postlude,
+                ..
+            } = variant_field_arms(e, false)?;
+            let FieldsCode {
+                prelude: prelude_mut,
+                exprs: arms_mut,
+                postlude: postlude_mut,
+                ..
+            } = variant_field_arms(e, true)?;
+            // Only enums with data carrying variants need room for showing the fields
+            let layout = if arms.is_empty() {
+                quote! { ::egui_inspect::egui::Ui::horizontal }
+            } else {
+                quote! { ::egui_inspect::egui::Ui::vertical }
+            };
+            extra_impls.extend(quote! {
+                impl #intro_generics ::egui_inspect::InspectEnum for #ty_ident #forward_generics #where_clauses {
+                    fn inspect_enum_mut(&mut self, ui: &mut ::egui_inspect::egui::Ui, ctx: &::egui_inspect::InspectCtx, display: ::egui_inspect::EnumDisplay) -> ::egui_inspect::InspectResponse {
+                        if ctx.read_only() {
+                            ::egui_inspect::Inspect::inspect(self, ui, ctx);
+                            return ::egui_inspect::InspectResponse::default();
+                        }
+                        let mut response = ::egui_inspect::InspectResponse::default();
+                        #layout(ui, |ui| {
+                            let sel_text = match self {
+                                #(#sel_name_match_exprs,)*
+                            };
+                            let radio = display == ::egui_inspect::EnumDisplay::Radio;
+                            let mut select = |ui: &mut ::egui_inspect::egui::Ui| {
+                                #({ #selectable_value_exprs })*
+                            };
+                            let re = match display {
+                                ::egui_inspect::EnumDisplay::Combo => {
+                                    ::egui_inspect::egui::ComboBox::from_id_salt(ctx.id()).selected_text(sel_text).show_ui(ui, select).response
+                                }
+                                ::egui_inspect::EnumDisplay::Radio => ui.vertical(select).response,
+                                // The buttons are packed together, so they look like a single widget
+                                ::egui_inspect::EnumDisplay::Toggle => {
+                                    ui.horizontal(|ui| {
+                                        ui.spacing_mut().item_spacing.x = 0.0;
+                                        select(ui);
+                                    }).response
+                                }
+                            };
+                            response |= ::egui_inspect::InspectResponse::from(&re);
+                            #type_doc_tooltip
+                            #(#prelude_mut)*
+                            #[allow(unreachable_patterns)]
+                            match self {
+                                #(#arms_mut)*
+                                _ => {}
+                            }
+                            #(#postlude_mut)*
+                        });
+                        response
+                    }
+                }
+            });
+            let display = container_attrs.display.unwrap_or(EnumDisplay::Combo);
+            (
+                quote! {
+                    #layout(ui, |ui| {
+                        let sel_text = match self {
+                            #(#sel_name_match_exprs,)*
+                        };
+                        let re = ui.label(sel_text);
+                        #type_doc_tooltip
+                        #(#prelude)*
+                        #[allow(unreachable_patterns)]
+                        match self {
+                            #(#arms)*
+                            _ => {}
+                        }
+                        #(#postlude)*
+                    });
+                },
+                quote! {
+                    ::egui_inspect::InspectEnum::inspect_enum_mut(self, ui, ctx, #display)
+                },
+            )
+        }
+        Data::Union(u) => {
+            let title = match &container_attrs.title {
+                Some(title) => quote! { #title },
+                None => quote! { stringify!(#ty_ident) },
+            };
+            let default_open = container_attrs.default_open;
+            let show = |body| {
+                quote! {
+                    let text = if ::egui_inspect::header_open(ui, ctx.id(), #default_open || ctx.options().default_open) {
+                        ::std::string::ToString::to_string(#title)
+                    } else {
+                        match ::egui_inspect::debug_preview(self) {
+                            ::core::option::Option::Some(preview) => format!("{} {}", #title, preview),
+                            ::core::option::Option::None => ::std::string::ToString::to_string(#title),
+                        }
+                    };
+                    let re = ::egui_inspect::egui::CollapsingHeader::new(text).id_salt(ctx.id()).default_open(#default_open || ctx.options().default_open).show(ui, |ui| {
+                        let prefix = "";
+                        let _ctx = ctx;
+                        #body
+                    }).header_response;
+                    #type_doc_tooltip
+                }
+            };
+            let show_mut = show(union_body(u, &container_attrs, true)?);
+            (
+                show(union_body(u, &container_attrs, false)?),
+                quote! {
+                    let mut response = ::egui_inspect::InspectResponse::default();
+                    #show_mut
+                    response
+                },
+            )
         }
-        Data::Union(_) => panic!("Unions are not supported"),
     };
-    let (intro_generics, forward_generics, where_clauses) = input.generics.split_for_impl();
+    // `Default` of the type itself is preferred, if it can be detected
+    let (has_default, default) = match generics.type_params().next() {
+        Some(_) => generic_default(&input.data)?,
+        None => None,
+    }
+    .unwrap_or_else(|| (quote! { false }, quote! { ::core::option::Option::None }));
+    let debug_fallback = debug_from_fields(&input.data, ty_ident)?;
     let expanded = quote! {
+        #legacy_attr_warnings
         impl #intro_generics ::egui_inspect::Inspect for #ty_ident #forward_generics #where_clauses {
-            fn inspect(&self, ui: &mut ::egui_inspect::egui::Ui, id_source: u64) {
-
-            }
-            fn inspect_mut(&mut self, ui: &mut ::egui_inspect::egui::Ui, id_source: u64) {
+            fn inspect(&self, ui: &mut ::egui_inspect::egui::Ui, ctx: &::egui_inspect::InspectCtx) {
+                if ::egui_inspect::max_depth_preview(ui, ctx, self) {
+                    return;
+                }
                 #ts
             }
+            fn inspect_mut(&mut self, ui: &mut ::egui_inspect::egui::Ui, ctx: &::egui_inspect::InspectCtx) -> ::egui_inspect::InspectResponse {
+                if ctx.read_only() || ctx.max_depth_reached() {
+                    ::egui_inspect::Inspect::inspect(self, ui, ctx);
+                    return ::egui_inspect::InspectResponse::default();
+                }
+                #ts_mut
+            }
+            fn default_value() -> ::core::option::Option<Self> {
+                use ::egui_inspect::{ProbeDefault as _, ProbeNoDefault as _};
+                if let ::core::option::Option::Some(value) = (&&::egui_inspect::DefaultProbe::<Self>(::core::marker::PhantomData)).maybe_default() {
+                    return ::core::option::Option::Some(value);
+                }
+                #default
+            }
+            fn has_default_value() -> bool {
+                use ::egui_inspect::{ProbeDefault as _, ProbeNoDefault as _};
+                if (&&::egui_inspect::DefaultProbe::<Self>(::core::marker::PhantomData)).has_default() {
+                    return true;
+                }
+                #has_default
+            }
+            fn fmt_debug(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::option::Option<::core::fmt::Result> {
+                use ::egui_inspect::{ProbeDebug as _, ProbeNoDebug as _};
+                if let ::core::option::Option::Some(result) = (&&::egui_inspect::DebugProbe::<Self>(::core::marker::PhantomData)).maybe_fmt(self, f) {
+                    return ::core::option::Option::Some(result);
+                }
+                #debug_fallback
+            }
         }
+        #extra_impls
     };
-    proc_macro::TokenStream::from(expanded)
+    Ok(expanded)
 }
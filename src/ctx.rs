@@ -0,0 +1,106 @@
+//! The context that is passed down the inspector tree
+
+use {
+    egui::Id,
+    std::{fmt::Display, hash::Hash},
+};
+
+/// Options that apply to a whole inspector tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InspectOptions {
+    /// Don't allow changing anything, even when inspecting mutably
+    pub read_only: bool,
+    /// Show integers in hexadecimal, with negative ones like `-0xa`
+    pub hex_ints: bool,
+    /// Values nested deeper than this only show a preview, instead of their contents
+    pub max_depth: Option<usize>,
+    /// Open the headers of structs and collections by default
+    pub default_open: bool,
+}
+
+/// The context of a value in the inspector tree.
+///
+/// It identifies the value, and carries the options of the tree, along with the path,
+/// depth and read-only state of the value.
+/// The context of a nested value is created with [`InspectCtx::child`].
+#[derive(Clone, Debug)]
+pub struct InspectCtx {
+    options: InspectOptions,
+    id: Id,
+    path: String,
+    depth: usize,
+    read_only: bool,
+}
+
+impl InspectCtx {
+    /// Creates the context for the root of an inspector tree, which is identified by `id`
+    pub fn new(id: Id, options: InspectOptions) -> Self {
+        Self {
+            options,
+            id,
+            path: String::new(),
+            depth: 0,
+            read_only: options.read_only,
+        }
+    }
+    /// The context of a nested value, like a field or an element of a collection
+    pub fn child(&self, name: impl Display + Hash) -> Self {
+        self.child_with_key(&name, &name)
+    }
+    /// The context of a nested value that's identified by `key`, like an entry of a map.
+    ///
+    /// `name` is used for the path. The id is derived from `key`, so it stays the same when
+    /// entries before it are added or removed.
+    pub fn child_with_key(&self, name: impl Display, key: impl Hash) -> Self {
+        let path = if self.path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.path, name)
+        };
+        Self {
+            options: self.options,
+            id: self.id.with(key),
+            path,
+            depth: self.depth + 1,
+            read_only: self.read_only,
+        }
+    }
+    /// The same context, but read-only if `read_only` is true
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only |= read_only;
+        self
+    }
+    /// The same context with a different id, for showing the same value more than once
+    #[doc(hidden)]
+    pub fn salted(&self, salt: impl Hash) -> Self {
+        Self {
+            id: self.id.with(salt),
+            ..self.clone()
+        }
+    }
+    /// The options of the inspector tree
+    pub fn options(&self) -> &InspectOptions {
+        &self.options
+    }
+    /// The id of the value, derived from the ids of its parents
+    pub fn id(&self) -> Id {
+        self.id
+    }
+    /// The path of the value from the root, like `entities.3.name`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    /// How deeply the value is nested. The root has a depth of 0.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+    /// Whether the value can't be changed, even when inspecting mutably
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+    /// Whether the value is nested as deeply as `InspectOptions::max_depth` allows,
+    /// so its contents aren't shown
+    pub fn max_depth_reached(&self) -> bool {
+        self.options.max_depth.is_some_and(|max| self.depth >= max)
+    }
+}
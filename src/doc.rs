@@ -0,0 +1,280 @@
+//! Rendering of doc comments, with basic markdown formatting
+
+use egui::{text::LayoutJob, Align, FontSelection, RichText, Ui};
+
+/// Shows a doc comment, rendering code spans, emphasis, lists, headings and code blocks.
+///
+/// The derive macro uses this for the tooltips of fields, variants and types.
+pub fn show_doc(ui: &mut Ui, doc: &str) {
+    // The block being collected, along with the bullet or number it starts with
+    let mut block: Option<(String, String)> = None;
+    let mut in_code_block = false;
+    for line in doc.lines() {
+        // Doc comments are usually written with a space after the `///`
+        let line = line.strip_prefix(' ').unwrap_or(line);
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            flush(ui, &mut block);
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            ui.label(RichText::new(line).code());
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush(ui, &mut block);
+            continue;
+        }
+        if let Some(heading) = trimmed.strip_prefix('#') {
+            flush(ui, &mut block);
+            ui.label(RichText::new(heading.trim_start_matches('#').trim()).strong());
+            continue;
+        }
+        if let Some((marker, item)) = list_item(trimmed) {
+            flush(ui, &mut block);
+            block = Some((marker, item.to_string()));
+            continue;
+        }
+        match &mut block {
+            Some((_, text)) => {
+                text.push(' ');
+                text.push_str(trimmed);
+            }
+            None => block = Some((String::new(), trimmed.to_string())),
+        }
+    }
+    flush(ui, &mut block);
+}
+
+/// Splits a list item into its marker and its text
+fn list_item(line: &str) -> Option<(String, &str)> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("• ".to_string(), item));
+    }
+    let (number, item) = line.split_once(". ")?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((format!("{number}. "), item))
+}
+
+/// Shows the collected paragraph or list item, if any
+fn flush(ui: &mut Ui, block: &mut Option<(String, String)>) {
+    let Some((marker, text)) = block.take() else {
+        return;
+    };
+    let mut job = LayoutJob::default();
+    let mut append = |text: RichText| {
+        text.append_to(&mut job, ui.style(), FontSelection::Default, Align::Min);
+    };
+    if !marker.is_empty() {
+        append(RichText::new(marker));
+    }
+    for span in inline_spans(&text) {
+        let mut rich = RichText::new(span.text);
+        if span.code {
+            rich = rich.code();
+        }
+        if span.strong {
+            rich = rich.strong();
+        }
+        if span.italics {
+            rich = rich.italics();
+        }
+        append(rich);
+    }
+    ui.label(job);
+}
+
+/// A run of text with the same formatting
+#[derive(Default)]
+struct Span {
+    text: String,
+    code: bool,
+    strong: bool,
+    italics: bool,
+}
+
+/// Splits markdown text into spans, according to code spans, emphasis and links
+fn inline_spans(text: &str) -> Vec<Span> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut spans = Vec::new();
+    let mut current = Span::default();
+    // The closing bracket of the link being shown, and where the link ends
+    let mut link: Option<(usize, usize)> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+        // Changes the formatting of the text that follows
+        let mut toggle = |current: &mut Span, f: fn(&mut Span) -> &mut bool| {
+            let mut new = Span::default();
+            (new.code, new.strong, new.italics) = (current.code, current.strong, current.italics);
+            *f(&mut new) ^= true;
+            spans.push(std::mem::replace(current, new));
+        };
+        match c {
+            // Links show their text, and intra-doc links lose their brackets
+            _ if link.is_some_and(|(close, _)| close == i) => {
+                if let Some((_, end)) = link.take() {
+                    i = end;
+                }
+            }
+            // Code spans only open if they are closed later on the line
+            '`' if current.code || chars[i + 1..].contains(&'`') => {
+                toggle(&mut current, |span| &mut span.code)
+            }
+            _ if current.code => current.text.push(c),
+            '*' if next == Some('*') => {
+                if current.strong || opens(&chars, i, "**") {
+                    toggle(&mut current, |span| &mut span.strong);
+                } else {
+                    current.text.push_str("**");
+                }
+                i += 1;
+            }
+            // Emphasis only opens if it's closed later
+            '*' if (!current.italics && opens(&chars, i, "*"))
+                || (current.italics && !prev.is_some_and(char::is_whitespace)) =>
+            {
+                toggle(&mut current, |span| &mut span.italics)
+            }
+            // `snake_case` identifiers are common in docs, so `_` only counts at word boundaries
+            '_' if (!current.italics
+                && !prev.is_some_and(char::is_alphanumeric)
+                && opens(&chars, i, "_"))
+                || (current.italics
+                    && !next.is_some_and(char::is_alphanumeric)
+                    && !prev.is_some_and(char::is_whitespace)) =>
+            {
+                toggle(&mut current, |span| &mut span.italics);
+            }
+            '[' if link.is_none() => match link_at(&chars, i) {
+                Some(found) => link = Some(found),
+                None => current.text.push(c),
+            },
+            _ => current.text.push(c),
+        }
+        i += 1;
+    }
+    spans.push(current);
+    spans.retain(|span| !span.text.is_empty());
+    spans
+}
+
+/// Whether the emphasis delimiter at `i` opens emphasis, because it's followed by text and
+/// a matching closing delimiter
+fn opens(chars: &[char], i: usize, delim: &str) -> bool {
+    let delim: Vec<char> = delim.chars().collect();
+    let start = i + delim.len();
+    if chars.get(start).is_none_or(|c| c.is_whitespace()) {
+        return false;
+    }
+    (start + 1..chars.len()).any(|j| {
+        chars[j..].starts_with(&delim)
+            && !chars[j - 1].is_whitespace()
+            // `_` only closes at the end of a word
+            && (delim != ['_'] || !chars.get(j + 1).is_some_and(|c| c.is_alphanumeric()))
+    })
+}
+
+/// Recognizes a `[text](url)` or ``[`Item`]`` link starting at `i`.
+///
+/// Returns the index of the closing bracket, and the index of the last character of the link.
+fn link_at(chars: &[char], i: usize) -> Option<(usize, usize)> {
+    // Brackets in code spans don't close the link
+    let mut in_code = false;
+    let close = (i + 1..chars.len()).find(|&j| match chars[j] {
+        '`' => {
+            in_code = !in_code;
+            false
+        }
+        ']' => !in_code,
+        _ => false,
+    })?;
+    if chars.get(close + 1) == Some(&'(') {
+        let end = (close + 2..chars.len()).find(|&j| chars[j] == ')')?;
+        return Some((close, end));
+    }
+    let inner = &chars[i + 1..close];
+    let intra_doc = inner.len() > 2 && inner.first() == Some(&'`') && inner.last() == Some(&'`');
+    intra_doc.then_some((close, close))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inline_spans, list_item};
+
+    /// The spans of `text`, as their text and whether they are code, strong and in italics
+    fn spans(text: &str) -> Vec<(String, bool, bool, bool)> {
+        inline_spans(text)
+            .into_iter()
+            .map(|span| (span.text, span.code, span.strong, span.italics))
+            .collect()
+    }
+
+    fn plain(text: &str) -> Vec<(String, bool, bool, bool)> {
+        vec![(text.to_string(), false, false, false)]
+    }
+
+    #[test]
+    fn brackets_without_link_are_kept() {
+        assert_eq!(spans("index a[0]"), plain("index a[0]"));
+        assert_eq!(spans("[x] and ]"), plain("[x] and ]"));
+    }
+
+    #[test]
+    fn links_show_their_text() {
+        assert_eq!(
+            spans("see [the docs](https://docs.rs) now"),
+            plain("see the docs now")
+        );
+        assert_eq!(
+            spans("see [`Inspect`]"),
+            vec![
+                ("see ".to_string(), false, false, false),
+                ("Inspect".to_string(), true, false, false),
+            ]
+        );
+        assert_eq!(
+            spans("[`a[0]`](url)"),
+            vec![("a[0]".to_string(), true, false, false)]
+        );
+    }
+
+    #[test]
+    fn unclosed_emphasis_is_literal() {
+        assert_eq!(spans("compute x * y"), plain("compute x * y"));
+        assert_eq!(spans("a * b * c"), plain("a * b * c"));
+        assert_eq!(spans("_private field"), plain("_private field"));
+        assert_eq!(spans("2 ** 3"), plain("2 ** 3"));
+        assert_eq!(spans("press the ` key"), plain("press the ` key"));
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(
+            spans("*a* **b** _c_ snake_case"),
+            vec![
+                ("a".to_string(), false, false, true),
+                (" ".to_string(), false, false, false),
+                ("b".to_string(), false, true, false),
+                (" ".to_string(), false, false, false),
+                ("c".to_string(), false, false, true),
+                (" snake_case".to_string(), false, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_items() {
+        assert_eq!(list_item("- item"), Some(("• ".to_string(), "item")));
+        assert_eq!(list_item("* item"), Some(("• ".to_string(), "item")));
+        assert_eq!(list_item("12. item"), Some(("12. ".to_string(), "item")));
+        assert_eq!(list_item("x. item"), None);
+        assert_eq!(list_item("*emphasis*"), None);
+        assert_eq!(list_item("3.14 is pi"), None);
+    }
+}
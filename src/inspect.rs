@@ -1,81 +1,292 @@
 use {
-    crate::ui_ext::UiExt as _,
-    egui::Ui,
+    crate::{
+        preview::{collection_header, copy_debug_text, max_depth_preview},
+        DebugText, InspectCtx, InspectResponse,
+    },
+    egui::{emath::Numeric, Ui},
     std::{
         collections::{HashMap, HashSet},
         ffi::OsString,
-        fmt::Debug,
+        fmt::{self, Debug},
+        hash::Hash,
         marker::PhantomData,
     },
 };
 
 /// Trait for inspecting a value of a type through egui.
-pub trait Inspect: Debug {
+pub trait Inspect {
     /// Inspect this value immutably.
     ///
-    /// `id_salt` provides a unique id that might be needed by egui.
-    fn inspect(&self, ui: &mut Ui, id_salt: u64);
+    /// `ctx` identifies this value in the inspector tree, and carries the options of the tree.
+    /// The contexts of nested values are derived from it, like `ctx.child(field_name)`.
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx);
     /// Inspect this value mutably
     ///
-    /// `id_salt` provides a unique id that might be needed by egui.
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        self.inspect(ui, id_salt);
+    /// `ctx` identifies this value in the inspector tree, and carries the options of the tree.
+    /// The contexts of nested values are derived from it, like `ctx.child(field_name)`.
+    /// If `ctx` is read-only, the value should be shown like with [`Inspect::inspect`].
+    /// The returned response tells whether the value was changed.
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        self.inspect(ui, ctx);
+        InspectResponse::default()
+    }
+    /// Constructs a new value, for adding elements to collections.
+    ///
+    /// Returns `None` if the type can't construct values, which is the default.
+    /// `#[derive(Inspect)]` implements this through `Default`, if the type implements it.
+    fn default_value() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+    /// Whether [`Inspect::default_value`] returns a value.
+    ///
+    /// Collections call this every frame to decide whether to offer adding elements, and only
+    /// construct the value when one is added. The default constructs a value to find out, so
+    /// override this if constructing values is expensive.
+    fn has_default_value() -> bool
+    where
+        Self: Sized,
+    {
+        Self::default_value().is_some()
     }
+    /// Formats this value like `Debug`, for previews and for copying it to the clipboard.
+    ///
+    /// Returns `None` if the type has no textual representation, which is the default.
+    /// `#[derive(Inspect)]` implements this through `Debug`, if the type implements it.
+    /// Containers can format their elements with [`DebugText`].
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        let _ = f;
+        None
+    }
+}
+
+/// Trait for showing the fields of a value, without a surrounding header.
+///
+/// `#[derive(Inspect)]` implements this for structs.
+/// It's used for showing `#[inspect(flatten)]` fields inline in their parent.
+pub trait InspectFields {
+    /// Show the fields immutably.
+    ///
+    /// `prefix` is prepended to the label of every field.
+    /// `ctx` is the context of this value, the contexts of the fields are derived from it.
+    fn inspect_fields(&self, ui: &mut Ui, prefix: &str, ctx: &InspectCtx);
+    /// Show the fields mutably.
+    ///
+    /// `prefix` is prepended to the label of every field.
+    /// `ctx` is the context of this value, the contexts of the fields are derived from it.
+    /// The returned response combines the responses of the fields.
+    fn inspect_fields_mut(
+        &mut self,
+        ui: &mut Ui,
+        prefix: &str,
+        ctx: &InspectCtx,
+    ) -> InspectResponse;
 }
 
+/// How the variant of an enum is selected, when inspecting it mutably.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnumDisplay {
+    /// A combo box, listing the variants in a popup
+    #[default]
+    Combo,
+    /// A radio button for each variant
+    Radio,
+    /// A row of toggle buttons, one for each variant
+    Toggle,
+}
+
+/// Trait for inspecting enums with a choice of variant selector.
+///
+/// `#[derive(Inspect)]` implements this for enums.
+/// It's used for `#[inspect(display = "...")]` on fields.
+pub trait InspectEnum: Inspect {
+    /// Inspect this value mutably, selecting the variant as given by `display`.
+    ///
+    /// `ctx` identifies this value in the inspector tree.
+    /// The returned response tells whether the value was changed.
+    fn inspect_enum_mut(
+        &mut self,
+        ui: &mut Ui,
+        ctx: &InspectCtx,
+        display: EnumDisplay,
+    ) -> InspectResponse;
+}
+
+/// Opts a union into `#[derive(Inspect)]`.
+///
+/// The derived impl reads the bytes and members of the union, which the compiler can't check,
+/// so deriving `Inspect` for a union requires implementing this trait.
+///
+/// # Safety
+///
+/// - All bytes of the union must always be initialized, like for `#[repr(C)]` unions of
+///   plain old data without padding.
+/// - With `#[inspect(active_member = ...)]`, the function must only return the name of a
+///   member that holds a valid value.
+/// - With `#[inspect(unsafe_view_all_members)]`, every member must be valid for any bit pattern.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` doesn't implement `InspectUnion`, which deriving `Inspect` for a union requires",
+    note = "make sure the union upholds the safety requirements, then add `unsafe impl egui_inspect::InspectUnion for {Self} {{}}`"
+)]
+pub unsafe trait InspectUnion {}
+
 impl Inspect for String {
-    fn inspect_mut(&mut self, ui: &mut Ui, _id_salt: u64) {
-        ui.text_edit_singleline(self);
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        if ctx.read_only() {
+            self.inspect(ui, ctx);
+            return InspectResponse::default();
+        }
+        ui.text_edit_singleline(self).into()
     }
 
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+    fn default_value() -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
+    }
+
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
         ui.label(self);
     }
 }
 
-trait InspectAddUi: Sized {
-    fn inspect_add_ui(ui: &mut Ui, vec: &mut Vec<Self>);
+/// Tells whether a type implements `Default`, without requiring it as a bound.
+///
+/// The derive macro uses this to build the payload of an enum variant when the user
+/// switches to it, and to implement [`Inspect::default_value`].
+/// Method resolution picks [`ProbeDefault`] if `T: Default`, because it's implemented
+/// for a reference to the probe, which is tried first.
+/// In generic code, `T: Default` only holds if it's a bound.
+#[doc(hidden)]
+pub struct DefaultProbe<T>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait ProbeDefault<T> {
+    fn has_default(&self) -> bool;
+    fn maybe_default(&self) -> Option<T>;
 }
 
-impl<T> InspectAddUi for T {
-    default fn inspect_add_ui(_ui: &mut Ui, _vec: &mut Vec<T>) {}
+impl<T: Default> ProbeDefault<T> for &DefaultProbe<T> {
+    fn has_default(&self) -> bool {
+        true
+    }
+    fn maybe_default(&self) -> Option<T> {
+        Some(T::default())
+    }
 }
 
-impl<T: Default> InspectAddUi for T {
-    fn inspect_add_ui(ui: &mut Ui, vec: &mut Vec<T>) {
-        if ui.button("+").clicked() {
-            vec.push(T::default())
-        }
+#[doc(hidden)]
+pub trait ProbeNoDefault<T> {
+    fn has_default(&self) -> bool;
+    fn maybe_default(&self) -> Option<T>;
+}
+
+impl<T> ProbeNoDefault<T> for DefaultProbe<T> {
+    fn has_default(&self) -> bool {
+        false
+    }
+    fn maybe_default(&self) -> Option<T> {
+        None
+    }
+}
+
+/// Tells whether a type implements `Debug`, without requiring it as a bound.
+///
+/// The derive macro uses this to implement [`Inspect::fmt_debug`].
+/// It works like [`DefaultProbe`].
+#[doc(hidden)]
+pub struct DebugProbe<T>(pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait ProbeDebug<T> {
+    fn maybe_fmt(&self, value: &T, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result>;
+}
+
+impl<T: Debug> ProbeDebug<T> for &DebugProbe<T> {
+    fn maybe_fmt(&self, value: &T, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(value, f))
+    }
+}
+
+#[doc(hidden)]
+pub trait ProbeNoDebug<T> {
+    fn maybe_fmt(&self, value: &T, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result>;
+}
+
+impl<T> ProbeNoDebug<T> for DebugProbe<T> {
+    fn maybe_fmt(&self, _value: &T, _f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        None
     }
 }
 
 impl<T: Inspect> Inspect for Vec<T> {
-    fn inspect_mut(&mut self, ui: &mut Ui, mut id_salt: u64) {
-        T::inspect_add_ui(ui, self);
-        if ui.button("🗑").on_hover_text("Clear").clicked() {
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        if ctx.read_only() || ctx.max_depth_reached() {
+            self.inspect(ui, ctx);
+            return InspectResponse::default();
+        }
+        let mut response = InspectResponse::default();
+        // Elements can only be added if there is a value to add
+        if T::has_default_value() {
+            let re = ui.button("+");
+            response |= InspectResponse::from(&re);
+            if re.clicked() {
+                if let Some(value) = T::default_value() {
+                    self.push(value);
+                    response.mark_changed();
+                }
+            }
+        }
+        let re = ui.button("🗑").on_hover_text("Clear");
+        response |= InspectResponse::from(&re);
+        if re.clicked() {
             self.clear();
+            response.mark_changed();
+        }
+        let title = collection_header(ui, ctx, format!("Vec [{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
+            .show(ui, |ui| {
+                for (i, item) in self.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::Label::new(i.to_string()).sense(egui::Sense::click()))
+                            .clicked()
+                        {
+                            copy_debug_text(ui, &*item);
+                        }
+                        response |= item.inspect_mut(ui, &ctx.child(i));
+                    });
+                }
+            });
+        response
+    }
+
+    fn default_value() -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(
+            f.debug_list()
+                .entries(self.iter().map(|item| DebugText(item)))
+                .finish(),
+        )
+    }
+
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        if max_depth_preview(ui, ctx, self) {
+            return;
         }
-        ui.inspect_iter_with_mut(
-            &format!("Vec [{}]", self.len()),
-            self,
-            &mut id_salt,
-            |ui, i, item, _id_salt| {
-                ui.horizontal(|ui| {
-                    if ui
-                        .add(egui::Label::new(i.to_string()).sense(egui::Sense::click()))
-                        .clicked()
-                    {
-                        ui.output_mut(|o| o.copied_text = format!("{:?}", item));
-                    }
-                    item.inspect_mut(ui, i as u64);
-                });
-            },
-        );
-    }
-
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
-        egui::CollapsingHeader::new(format!("Vec [{}]", self.len()))
-            .id_salt(id_salt)
+        let title = collection_header(ui, ctx, format!("Vec [{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
                 for (i, item) in self.iter().enumerate() {
                     ui.horizontal(|ui| {
@@ -83,9 +294,9 @@ impl<T: Inspect> Inspect for Vec<T> {
                             .add(egui::Label::new(i.to_string()).sense(egui::Sense::click()))
                             .clicked()
                         {
-                            ui.output_mut(|o| o.copied_text = format!("{:?}", item));
+                            copy_debug_text(ui, &*item);
                         }
-                        item.inspect(ui, i as u64);
+                        item.inspect(ui, &ctx.child(i));
                     });
                 }
             });
@@ -93,65 +304,107 @@ impl<T: Inspect> Inspect for Vec<T> {
 }
 
 impl<T: Inspect> Inspect for Option<T> {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
         match self {
             None => {
                 ui.label("None");
+                InspectResponse::default()
             }
-            Some(t) => {
-                t.inspect_mut(ui, id_salt);
-            }
+            Some(t) => t.inspect_mut(ui, ctx),
         }
     }
 
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
+    fn default_value() -> Option<Self> {
+        Some(None)
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(match self {
+            None => f.write_str("None"),
+            Some(t) => f.debug_tuple("Some").field(&DebugText(t)).finish(),
+        })
+    }
+
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
         match self {
             None => {
                 ui.label("None");
             }
             Some(t) => {
-                t.inspect(ui, id_salt);
+                t.inspect(ui, ctx);
             }
         }
     }
 }
 
 impl Inspect for OsString {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        self.inspect(ui, id_salt);
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
+        ui.label(format!("(OsString) {}", self.to_string_lossy()));
     }
 
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
-        ui.label(format!("(OsString) {}", self.to_string_lossy()));
+    fn default_value() -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
     }
 }
 
-impl<T: Inspect> Inspect for HashSet<T> {
-    fn inspect(&self, ui: &mut Ui, mut id_salt: u64) {
-        egui::CollapsingHeader::new("HashSet")
-            .id_salt(id_salt)
+impl<T: Inspect + Hash> Inspect for HashSet<T> {
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        if max_depth_preview(ui, ctx, self) {
+            return;
+        }
+        let title = collection_header(ui, ctx, format!("HashSet [{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
-                for item in self.iter() {
-                    ui.inspect(item, &mut id_salt);
+                for (i, item) in self.iter().enumerate() {
+                    item.inspect(ui, &ctx.child_with_key(i, item));
                 }
             });
     }
+
+    fn default_value() -> Option<Self> {
+        Some(Self::new())
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(
+            f.debug_set()
+                .entries(self.iter().map(|item| DebugText(item)))
+                .finish(),
+        )
+    }
 }
 
 impl<T: Inspect> Inspect for &mut T {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        (*self).inspect_mut(ui, id_salt)
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        (*self).inspect_mut(ui, ctx)
     }
 
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
-        (**self).inspect(ui, id_salt)
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        (**self).inspect(ui, ctx)
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        (**self).fmt_debug(f)
     }
 }
 
 impl<T: Inspect, const N: usize> Inspect for [T; N] {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        egui::CollapsingHeader::new(format!("array[{}]", self.len()))
-            .id_salt(id_salt)
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        if ctx.read_only() || ctx.max_depth_reached() {
+            self.inspect(ui, ctx);
+            return InspectResponse::default();
+        }
+        let mut response = InspectResponse::default();
+        let title = collection_header(ui, ctx, format!("array[{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
                 for (i, item) in self.iter_mut().enumerate() {
                     ui.horizontal(|ui| {
@@ -159,17 +412,23 @@ impl<T: Inspect, const N: usize> Inspect for [T; N] {
                             .add(egui::Label::new(i.to_string()).sense(egui::Sense::click()))
                             .clicked()
                         {
-                            ui.output_mut(|o| o.copied_text = format!("{:?}", item));
+                            copy_debug_text(ui, &*item);
                         }
-                        item.inspect_mut(ui, i as u64);
+                        response |= item.inspect_mut(ui, &ctx.child(i));
                     });
                 }
             });
+        response
     }
 
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
-        egui::CollapsingHeader::new(format!("array[{}]", self.len()))
-            .id_salt(id_salt)
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        if max_depth_preview(ui, ctx, self) {
+            return;
+        }
+        let title = collection_header(ui, ctx, format!("array[{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
                 for (i, item) in self.iter().enumerate() {
                     ui.horizontal(|ui| {
@@ -177,66 +436,117 @@ impl<T: Inspect, const N: usize> Inspect for [T; N] {
                             .add(egui::Label::new(i.to_string()).sense(egui::Sense::click()))
                             .clicked()
                         {
-                            ui.output_mut(|o| o.copied_text = format!("{:?}", item));
+                            copy_debug_text(ui, &*item);
                         }
-                        item.inspect(ui, i as u64);
+                        item.inspect(ui, &ctx.child(i));
                     });
                 }
             });
     }
+
+    fn default_value() -> Option<Self> {
+        let items: Vec<T> = (0..N).map(|_| T::default_value()).collect::<Option<_>>()?;
+        items.try_into().ok()
+    }
+
+    fn has_default_value() -> bool {
+        T::has_default_value()
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(
+            f.debug_list()
+                .entries(self.iter().map(|item| DebugText(item)))
+                .finish(),
+        )
+    }
 }
 
 impl<T: Inspect, const N: usize> Inspect for Box<[T; N]> {
-    fn inspect(&self, ui: &mut Ui, mut id_salt: u64) {
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
         let arr: &[T; N] = self;
-        ui.inspect(arr, &mut id_salt);
+        arr.inspect(ui, ctx);
     }
-    fn inspect_mut(&mut self, ui: &mut Ui, mut id_salt: u64) {
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
         let arr: &mut [T; N] = self;
-        ui.inspect(arr, &mut id_salt);
+        arr.inspect_mut(ui, ctx)
+    }
+    fn default_value() -> Option<Self> {
+        <[T; N]>::default_value().map(Box::new)
+    }
+    fn has_default_value() -> bool {
+        <[T; N]>::has_default_value()
+    }
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        (**self).fmt_debug(f)
     }
 }
 
-impl<K: Debug, V: Inspect, S> Inspect for HashMap<K, V, S> {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        egui::CollapsingHeader::new(format!("HashMap [{}]", self.len()))
-            .id_salt(id_salt)
+// There is no `default_value`, because that would require `S: Default`, which would rule out
+// inspecting maps with other hashers
+impl<K: Debug + Hash, V: Inspect, S> Inspect for HashMap<K, V, S> {
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        if ctx.read_only() || ctx.max_depth_reached() {
+            self.inspect(ui, ctx);
+            return InspectResponse::default();
+        }
+        let mut response = InspectResponse::default();
+        let title = collection_header(ui, ctx, format!("HashMap [{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
-                for (i, (k, v)) in self.iter_mut().enumerate() {
+                for (k, v) in self.iter_mut() {
                     ui.horizontal(|ui| {
+                        let name = format!("{:?}", k);
                         if ui
-                            .add(egui::Label::new(format!("{:?}", k)).sense(egui::Sense::click()))
+                            .add(egui::Label::new(name.as_str()).sense(egui::Sense::click()))
                             .clicked()
                         {
-                            ui.output_mut(|o| o.copied_text = format!("{:?}", v));
+                            copy_debug_text(ui, &*v);
                         }
-                        v.inspect_mut(ui, i as u64);
+                        response |= v.inspect_mut(ui, &ctx.child_with_key(name, k));
                     });
                 }
             });
+        response
     }
 
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
-        egui::CollapsingHeader::new(format!("HashMap [{}]", self.len()))
-            .id_salt(id_salt)
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        if max_depth_preview(ui, ctx, self) {
+            return;
+        }
+        let title = collection_header(ui, ctx, format!("HashMap [{}]", self.len()), self);
+        egui::CollapsingHeader::new(title)
+            .id_salt(ctx.id())
+            .default_open(ctx.options().default_open)
             .show(ui, |ui| {
-                for (i, (k, v)) in self.iter().enumerate() {
+                for (k, v) in self.iter() {
                     ui.horizontal(|ui| {
+                        let name = format!("{:?}", k);
                         if ui
-                            .add(egui::Label::new(format!("{:?}", k)).sense(egui::Sense::click()))
+                            .add(egui::Label::new(name.as_str()).sense(egui::Sense::click()))
                             .clicked()
                         {
-                            ui.output_mut(|o| o.copied_text = format!("{:?}", v));
+                            copy_debug_text(ui, &*v);
                         }
-                        v.inspect(ui, i as u64);
+                        v.inspect(ui, &ctx.child_with_key(name, k));
                     });
                 }
             });
     }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(
+            f.debug_map()
+                .entries(self.iter().map(|(k, v)| (k, DebugText(v))))
+                .finish(),
+        )
+    }
 }
 
 impl Inspect for &'_ str {
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
         if ui
             .add(egui::Label::new(*self).sense(egui::Sense::click()))
             .clicked()
@@ -244,57 +554,180 @@ impl Inspect for &'_ str {
             ui.output_mut(|o| o.copied_text = self.to_string())
         }
     }
+
+    fn default_value() -> Option<Self> {
+        Some("")
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
+    }
 }
 
 impl Inspect for bool {
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
         let mut value = *self;
         ui.checkbox(&mut value, "");
     }
-    fn inspect_mut(&mut self, ui: &mut Ui, _id_salt: u64) {
-        ui.checkbox(self, "");
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        if ctx.read_only() {
+            self.inspect(ui, ctx);
+            return InspectResponse::default();
+        }
+        ui.checkbox(self, "").into()
+    }
+    fn default_value() -> Option<Self> {
+        Some(false)
+    }
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
+    }
+}
+
+/// A `DragValue` for a number, following the options of the inspector tree.
+///
+/// Integers are shown in hexadecimal if `InspectOptions::hex_ints` is set,
+/// in the same form as read-only integers.
+/// The derive macro uses this for fields with numeric options.
+#[doc(hidden)]
+pub fn drag_value<'a, N: Numeric>(value: &'a mut N, ctx: &InspectCtx) -> egui::DragValue<'a> {
+    let drag_value = egui::DragValue::new(value);
+    if N::INTEGRAL && ctx.options().hex_ints {
+        drag_value
+            .custom_formatter(|n, _| hex_string(n as i128))
+            .custom_parser(parse_hex)
+    } else {
+        drag_value
+    }
+}
+
+/// Formats an integer in hexadecimal, with negative values as `-0xa`
+fn hex_string(n: i128) -> String {
+    if n < 0 {
+        format!("-{:#x}", n.unsigned_abs())
+    } else {
+        format!("{:#x}", n)
     }
 }
 
+/// Parses the output of `hex_string`, with the `0x` being optional
+fn parse_hex(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (negative, text) = match text.strip_prefix(['-', '\u{2212}']) {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    let n = i128::from_str_radix(text, 16).ok()?;
+    Some(if negative { -n } else { n } as f64)
+}
+
 macro_rules! impl_num_inspect {
-    ($($ty:ty),*) => {
+    (ints: $($ty:ty),*) => {
+        $(impl Inspect for $ty {
+            fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+                if ctx.read_only() {
+                    self.inspect(ui, ctx);
+                    return InspectResponse::default();
+                }
+                ui.add(drag_value(self, ctx)).into()
+            }
+            fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+                if ctx.options().hex_ints {
+                    ui.label(hex_string(*self as i128));
+                } else {
+                    ui.label(self.to_string());
+                }
+            }
+            fn default_value() -> Option<Self> {
+                Some(0)
+            }
+            fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+                Some(Debug::fmt(self, f))
+            }
+        })*
+    };
+    (floats: $($ty:ty),*) => {
         $(impl Inspect for $ty {
-            fn inspect_mut(&mut self, ui: &mut Ui, _id_salt: u64) {
-                ui.add(egui::DragValue::new(self));
+            fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+                if ctx.read_only() {
+                    self.inspect(ui, ctx);
+                    return InspectResponse::default();
+                }
+                ui.add(egui::DragValue::new(self)).into()
             }
-            fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+            fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
                 ui.label(self.to_string());
             }
+            fn default_value() -> Option<Self> {
+                Some(0.0)
+            }
+            fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+                Some(Debug::fmt(self, f))
+            }
         })*
     };
 }
 
-impl_num_inspect!(i8, u8, i16, u16, i32, u32, i64, u64, f32, f64, usize, isize);
+impl_num_inspect!(ints: i8, u8, i16, u16, i32, u32, i64, u64, usize, isize);
+impl_num_inspect!(floats: f32, f64);
 
 impl<T, U> Inspect for (T, U)
 where
     T: Inspect,
     U: Inspect,
 {
-    fn inspect_mut(&mut self, ui: &mut Ui, id_salt: u64) {
-        self.0.inspect_mut(ui, id_salt);
-        self.1.inspect_mut(ui, id_salt);
+    fn inspect_mut(&mut self, ui: &mut Ui, ctx: &InspectCtx) -> InspectResponse {
+        self.0.inspect_mut(ui, &ctx.child(0)) | self.1.inspect_mut(ui, &ctx.child(1))
+    }
+
+    fn inspect(&self, ui: &mut Ui, ctx: &InspectCtx) {
+        self.0.inspect(ui, &ctx.child(0));
+        self.1.inspect(ui, &ctx.child(1));
+    }
+
+    fn default_value() -> Option<Self> {
+        Some((T::default_value()?, U::default_value()?))
+    }
+
+    fn has_default_value() -> bool {
+        T::has_default_value() && U::has_default_value()
     }
 
-    fn inspect(&self, ui: &mut Ui, id_salt: u64) {
-        self.0.inspect(ui, id_salt);
-        self.1.inspect(ui, id_salt);
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(
+            f.debug_tuple("")
+                .field(&DebugText(&self.0))
+                .field(&DebugText(&self.1))
+                .finish(),
+        )
     }
 }
 
 impl<T> Inspect for PhantomData<T> {
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
         ui.label("PhantomData");
     }
+
+    fn default_value() -> Option<Self> {
+        Some(PhantomData)
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
+    }
 }
 
 impl Inspect for () {
-    fn inspect(&self, ui: &mut Ui, _id_salt: u64) {
+    fn inspect(&self, ui: &mut Ui, _ctx: &InspectCtx) {
         ui.label("()");
     }
+
+    fn default_value() -> Option<Self> {
+        Some(())
+    }
+
+    fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> Option<fmt::Result> {
+        Some(Debug::fmt(self, f))
+    }
 }
@@ -3,17 +3,35 @@
 //!
 //! Optionally provides a derive macro for your own types as well.
 
-#![feature(specialization)]
 #![warn(missing_docs)]
 
+mod ctx;
+mod doc;
 mod inspect;
+mod preview;
+mod response;
 mod ui_ext;
 
 /// Re-export of egui. Derive codegen refers to this.
 pub use egui;
 #[cfg(feature = "derive")]
 pub use egui_inspect_derive as derive;
-pub use {inspect::Inspect, ui_ext::UiExt};
+pub use {
+    ctx::{InspectCtx, InspectOptions},
+    doc::show_doc,
+    inspect::{EnumDisplay, Inspect, InspectEnum, InspectFields, InspectUnion},
+    preview::{debug_text, DebugText},
+    response::InspectResponse,
+    ui_ext::UiExt,
+};
+#[doc(hidden)]
+pub use {
+    inspect::{
+        drag_value, DebugProbe, DefaultProbe, ProbeDebug, ProbeDefault, ProbeNoDebug,
+        ProbeNoDefault,
+    },
+    preview::{copy_debug_text, debug_preview, header_open, max_depth_preview, DebugFn},
+};
 
 /// Helper macro to help you quickly inspect variables
 ///
@@ -36,36 +54,126 @@ pub use {inspect::Inspect, ui_ext::UiExt};
 macro_rules! inspect {(
     $ui:expr, $($rest:tt)*
 ) => ({
-    let mut id_source = 0;
-    $crate::_egui_inspect_helper! { $ui id_source $($rest)* }
+    // The values are identified by their names, under the id of the `Ui`
+    let id = $crate::egui::Ui::id($ui);
+    $crate::_egui_inspect_helper! { $ui id $($rest)* }
 })}
 
 #[macro_export]
 #[doc(hidden)]
 macro_rules! _egui_inspect_helper {
-    ($ui:tt $id_source:tt) => ();
+    ($ui:tt $id:tt) => ();
 
     (
-        $ui:tt $id_source:tt
+        $ui:tt $id:tt
         $name:literal : $arg:expr $(, $($rest:tt)* )?
     ) => (
         $crate::UiExt::property_mut(
-            $ui, $name, &mut $arg, &mut $id_source
+            $ui, $name, &mut $arg, $id.with($name)
         );
         $($crate::_egui_inspect_helper! {
-            $ui $id_source $($rest)*
+            $ui $id $($rest)*
         })?
     );
 
     (
-        $ui:tt $id_source:tt
+        $ui:tt $id:tt
         $arg:expr $(, $($rest:tt)* )?
     ) => (
         $crate::UiExt::property_mut(
-            $ui, ::core::stringify!($arg), &mut $arg, &mut $id_source
+            $ui, ::core::stringify!($arg), &mut $arg, $id.with(::core::stringify!($arg))
         );
         $($crate::_egui_inspect_helper! {
-            $ui $id_source $($rest)*
+            $ui $id $($rest)*
         })?
     );
 }
+
+/// Implements [`InspectFields`] and [`Inspect`] for a struct, by listing the fields to show.
+///
+/// This is a lightweight alternative to the derive macro, for quick cases.
+/// The listed fields have to implement [`Inspect`].
+/// Previews and copied text of the value are made up of the listed fields.
+/// If the type implements `Default`, it's used for adding values to collections.
+///
+/// Usage:
+///
+/// ```no_run
+/// struct Vec3 {
+///     x: f32,
+///     y: f32,
+///     z: f32,
+/// }
+///
+/// egui_inspect::impl_inspect_fields!(Vec3 { x, y, z });
+/// ```
+///
+/// Note that the orphan rule still applies, so the type has to be defined in your crate.
+/// For foreign types, see `#[inspect(remote = "...")]` of the derive macro.
+#[macro_export]
+macro_rules! impl_inspect_fields {
+    ($ty:ty { $($field:tt),* $(,)? }) => {
+        impl $crate::InspectFields for $ty {
+            fn inspect_fields(&self, ui: &mut $crate::egui::Ui, prefix: &str, ctx: &$crate::InspectCtx) {
+                $(
+                    ui.horizontal(|ui| {
+                        ui.label(::std::format!("{}{}", prefix, ::core::stringify!($field)));
+                        $crate::Inspect::inspect(&self.$field, ui, &ctx.child(::core::stringify!($field)));
+                    });
+                )*
+            }
+            fn inspect_fields_mut(&mut self, ui: &mut $crate::egui::Ui, prefix: &str, ctx: &$crate::InspectCtx) -> $crate::InspectResponse {
+                let mut response = $crate::InspectResponse::default();
+                $(
+                    ui.horizontal(|ui| {
+                        ui.label(::std::format!("{}{}", prefix, ::core::stringify!($field)));
+                        response |= $crate::Inspect::inspect_mut(&mut self.$field, ui, &ctx.child(::core::stringify!($field)));
+                    });
+                )*
+                response
+            }
+        }
+        impl $crate::Inspect for $ty {
+            fn inspect(&self, ui: &mut $crate::egui::Ui, ctx: &$crate::InspectCtx) {
+                if $crate::max_depth_preview(ui, ctx, self) {
+                    return;
+                }
+                $crate::egui::CollapsingHeader::new(::core::stringify!($ty))
+                    .id_salt(ctx.id())
+                    .default_open(ctx.options().default_open)
+                    .show(ui, |ui| {
+                        $crate::InspectFields::inspect_fields(self, ui, "", ctx);
+                    });
+            }
+            fn inspect_mut(&mut self, ui: &mut $crate::egui::Ui, ctx: &$crate::InspectCtx) -> $crate::InspectResponse {
+                if ctx.read_only() || ctx.max_depth_reached() {
+                    $crate::Inspect::inspect(self, ui, ctx);
+                    return $crate::InspectResponse::default();
+                }
+                let mut response = $crate::InspectResponse::default();
+                $crate::egui::CollapsingHeader::new(::core::stringify!($ty))
+                    .id_salt(ctx.id())
+                    .default_open(ctx.options().default_open)
+                    .show(ui, |ui| {
+                        response = $crate::InspectFields::inspect_fields_mut(self, ui, "", ctx);
+                    });
+                response
+            }
+            fn default_value() -> ::core::option::Option<Self> {
+                use $crate::{ProbeDefault as _, ProbeNoDefault as _};
+                (&&$crate::DefaultProbe::<Self>(::core::marker::PhantomData)).maybe_default()
+            }
+            fn has_default_value() -> bool {
+                use $crate::{ProbeDefault as _, ProbeNoDefault as _};
+                (&&$crate::DefaultProbe::<Self>(::core::marker::PhantomData)).has_default()
+            }
+            fn fmt_debug(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::option::Option<::core::fmt::Result> {
+                ::core::option::Option::Some(
+                    f.debug_struct(::core::stringify!($ty))
+                        $(.field(::core::stringify!($field), &$crate::DebugText(&self.$field)))*
+                        .finish()
+                )
+            }
+        }
+    };
+}
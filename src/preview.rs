@@ -0,0 +1,155 @@
+//! Previews of values, shown on collapsed headers
+
+use {
+    crate::{Inspect, InspectCtx},
+    egui::{collapsing_header::CollapsingState, Id, Ui},
+    std::{
+        cell::Cell,
+        fmt::{self, Debug, Write},
+    },
+};
+
+/// Maximum number of characters of a `Debug` preview
+const PREVIEW_LEN: usize = 48;
+
+/// Formats an [`Inspect`] value with [`Inspect::fmt_debug`], so it can be used where
+/// `Debug` is expected.
+///
+/// Values without a textual representation are written as `_`.
+/// This is useful for implementing [`Inspect::fmt_debug`] for containers.
+pub struct DebugText<'a>(pub &'a dyn Inspect);
+
+impl Debug for DebugText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_debug(f).unwrap_or_else(|| f.write_str("_"))
+    }
+}
+
+/// Formats a value with a closure, for the derived [`Inspect::fmt_debug`].
+#[doc(hidden)]
+pub struct DebugFn<F>(pub F);
+
+impl<F: Fn(&mut fmt::Formatter<'_>) -> fmt::Result> Debug for DebugFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+/// Formats a value, remembering whether it has a textual representation at all
+struct Probe<'a> {
+    value: &'a dyn Inspect,
+    missing: Cell<bool>,
+}
+
+impl Debug for Probe<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_debug(f).unwrap_or_else(|| {
+            self.missing.set(true);
+            Ok(())
+        })
+    }
+}
+
+/// Writes the textual representation of `value` to `out`.
+///
+/// Returns `false` if `value` has none.
+fn write_debug(out: &mut impl Write, value: &dyn Inspect) -> bool {
+    let probe = Probe {
+        value,
+        missing: Cell::new(false),
+    };
+    // Errors are left to the writer, like the truncation of previews
+    let _ = write!(out, "{probe:?}");
+    !probe.missing.get()
+}
+
+/// Returns the textual representation of `value`, as given by [`Inspect::fmt_debug`].
+///
+/// Returns `None` if `value` has none.
+pub fn debug_text(value: &dyn Inspect) -> Option<String> {
+    let mut text = String::new();
+    write_debug(&mut text, value).then_some(text)
+}
+
+/// Copies the textual representation of `value` to the clipboard, if it has one
+pub fn copy_debug_text(ui: &Ui, value: &dyn Inspect) {
+    if let Some(text) = debug_text(value) {
+        ui.output_mut(|o| o.copied_text = text);
+    }
+}
+
+/// Returns the textual representation of `value`, truncated to a short preview.
+///
+/// Formatting stops as soon as the preview is long enough, so large values are cheap to preview.
+/// Returns `None` if `value` has no textual representation.
+pub fn debug_preview(value: &dyn Inspect) -> Option<String> {
+    let mut preview = Preview {
+        text: String::new(),
+        len: 0,
+        truncated: false,
+    };
+    if !write_debug(&mut preview, value) {
+        return None;
+    }
+    if preview.truncated {
+        preview.text.push('…');
+    }
+    Some(preview.text)
+}
+
+/// Collects formatted text, up to `PREVIEW_LEN` characters
+struct Preview {
+    text: String,
+    len: usize,
+    truncated: bool,
+}
+
+impl Write for Preview {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == PREVIEW_LEN {
+                self.truncated = true;
+                return Err(fmt::Error);
+            }
+            self.text.push(c);
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the `egui::CollapsingHeader` with the given id salt is open
+pub fn header_open(ui: &Ui, id_salt: Id, default_open: bool) -> bool {
+    // This is how `egui::CollapsingHeader` turns its id salt into an id
+    let id = ui.make_persistent_id(Id::new(id_salt));
+    CollapsingState::load_with_default_open(ui.ctx(), id, default_open).is_open()
+}
+
+/// Shows a preview of `value` instead of its contents, if `ctx` is as deep as allowed.
+///
+/// Returns whether the preview was shown.
+pub fn max_depth_preview(ui: &mut Ui, ctx: &InspectCtx, value: &dyn Inspect) -> bool {
+    if !ctx.max_depth_reached() {
+        return false;
+    }
+    ui.label(debug_preview(value).unwrap_or_else(|| "…".into()))
+        .on_hover_text("Maximum inspection depth reached");
+    true
+}
+
+/// The text of a collection header, which is followed by a preview of the collection
+/// while collapsed.
+pub(crate) fn collection_header(
+    ui: &Ui,
+    ctx: &InspectCtx,
+    title: String,
+    value: &dyn Inspect,
+) -> String {
+    if header_open(ui, ctx.id(), ctx.options().default_open) {
+        return title;
+    }
+    match debug_preview(value) {
+        Some(preview) => format!("{title} {preview}"),
+        None => title,
+    }
+}
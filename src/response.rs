@@ -0,0 +1,76 @@
+use {
+    egui::Response,
+    std::ops::{BitOr, BitOrAssign},
+};
+
+/// The result of inspecting a value mutably.
+///
+/// Responses of nested values are combined with `|`, so a response tells whether any part
+/// of the value changed, is hovered or has keyboard focus.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InspectResponse {
+    changed: bool,
+    hovered: bool,
+    has_focus: bool,
+}
+
+impl InspectResponse {
+    /// Whether the value was changed through the inspector this frame
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+    /// Whether the pointer is hovering a widget of the value
+    pub fn hovered(&self) -> bool {
+        self.hovered
+    }
+    /// Whether a widget of the value has keyboard focus
+    pub fn has_focus(&self) -> bool {
+        self.has_focus
+    }
+    /// Report that the value was changed
+    pub fn mark_changed(&mut self) {
+        self.changed = true;
+    }
+}
+
+impl From<&Response> for InspectResponse {
+    fn from(re: &Response) -> Self {
+        Self {
+            changed: re.changed(),
+            hovered: re.hovered(),
+            has_focus: re.has_focus(),
+        }
+    }
+}
+
+impl From<Response> for InspectResponse {
+    fn from(re: Response) -> Self {
+        Self::from(&re)
+    }
+}
+
+/// Custom inspect functions that don't report anything don't change anything, as far as
+/// the inspector can tell.
+impl From<()> for InspectResponse {
+    fn from((): ()) -> Self {
+        Self::default()
+    }
+}
+
+impl BitOr for InspectResponse {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            changed: self.changed || rhs.changed,
+            hovered: self.hovered || rhs.hovered,
+            has_focus: self.has_focus || rhs.has_focus,
+        }
+    }
+}
+
+impl BitOrAssign for InspectResponse {
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
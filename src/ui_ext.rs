@@ -1,12 +1,17 @@
-use {crate::inspect::Inspect, egui::Ui};
+use {
+    crate::{
+        inspect::Inspect, preview::copy_debug_text, InspectCtx, InspectOptions, InspectResponse,
+    },
+    egui::{Id, Ui},
+};
 
 macro_rules! inspect_iter_with_body {
-    ($self:expr, $title:expr, $into_iter:expr, $id_salt:expr, $fun:expr) => {
+    ($self:expr, $title:expr, $into_iter:expr, $id:expr, $fun:expr) => {
         egui::CollapsingHeader::new($title)
-            .id_salt(*$id_salt)
+            .id_salt($id)
             .show($self, |ui| {
                 for (i, item) in $into_iter.into_iter().enumerate() {
-                    $fun(ui, i, item, $id_salt);
+                    $fun(ui, i, item, $id.with(i));
                 }
             });
     };
@@ -15,84 +20,88 @@ macro_rules! inspect_iter_with_body {
 /// Extension trait for `egui::Ui`. Provides helper methods for inspecting values.
 pub trait UiExt {
     /// Inspect a single value immutably.
-    /// `id_salt` is used to generate unique ids for egui.
-    fn inspect<T: Inspect>(&mut self, what: &T, id_salt: &mut u64);
+    /// `id` identifies the value in the inspector tree.
+    fn inspect<T: Inspect>(&mut self, what: &T, id: Id);
     /// Inspect a single value mutably.
-    /// `id_salt` is used to generate unique ids for egui.
-    fn inspect_mut<T: Inspect>(&mut self, what: &mut T, id_salt: &mut u64);
-    /// Inspect an iterator immutably.
-    /// `id_salt` is used to generate unique ids for egui.
-    fn inspect_iter_with<'a, I, T, F>(
+    /// `id` identifies the value in the inspector tree.
+    /// The returned response tells whether the value was changed.
+    fn inspect_mut<T: Inspect>(&mut self, what: &mut T, id: Id) -> InspectResponse;
+    /// Inspect a single value immutably, with the given options for the whole inspector tree.
+    /// `id` identifies the value in the inspector tree.
+    fn inspect_with_options<T: Inspect>(&mut self, what: &T, id: Id, options: InspectOptions);
+    /// Inspect a single value mutably, with the given options for the whole inspector tree.
+    /// `id` identifies the value in the inspector tree.
+    /// The returned response tells whether the value was changed.
+    fn inspect_mut_with_options<T: Inspect>(
         &mut self,
-        title: &str,
-        into_iter: I,
-        id_salt: &mut u64,
-        fun: F,
-    ) where
+        what: &mut T,
+        id: Id,
+        options: InspectOptions,
+    ) -> InspectResponse;
+    /// Inspect an iterator immutably.
+    /// `id` identifies the iterator, `fun` gets the id of each item, derived from its index.
+    fn inspect_iter_with<'a, I, T, F>(&mut self, title: &str, into_iter: I, id: Id, fun: F)
+    where
         I: IntoIterator<Item = &'a T>,
         T: 'a,
-        F: FnMut(&mut Ui, usize, &T, &mut u64);
+        F: FnMut(&mut Ui, usize, &T, Id);
     /// Inspect an iterator mutably.
-    /// `id_salt` is used to generate unique ids for egui.
-    fn inspect_iter_with_mut<'a, I, T, F>(
-        &mut self,
-        title: &str,
-        into_iter: I,
-        id_salt: &mut u64,
-        fun: F,
-    ) where
+    /// `id` identifies the iterator, `fun` gets the id of each item, derived from its index.
+    fn inspect_iter_with_mut<'a, I, T, F>(&mut self, title: &str, into_iter: I, id: Id, fun: F)
+    where
         I: IntoIterator<Item = &'a mut T>,
         T: 'a,
-        F: FnMut(&mut Ui, usize, &mut T, &mut u64);
+        F: FnMut(&mut Ui, usize, &mut T, Id);
     /// Inspect a struct field mutably.
-    /// `id_salt` is used to generate unique ids for egui.
-    fn property_mut<T: Inspect>(&mut self, name: &str, what: &mut T, id_salt: &mut u64);
+    /// `id` identifies the value in the inspector tree.
+    /// The returned response tells whether the value was changed.
+    fn property_mut<T: Inspect>(&mut self, name: &str, what: &mut T, id: Id) -> InspectResponse;
 }
 
 impl UiExt for Ui {
-    fn inspect<T: Inspect>(&mut self, what: &T, id_salt: &mut u64) {
-        what.inspect(self, *id_salt);
-        *id_salt += 1;
+    fn inspect<T: Inspect>(&mut self, what: &T, id: Id) {
+        self.inspect_with_options(what, id, InspectOptions::default());
     }
-    fn inspect_iter_with<'a, I, T, F>(
-        &mut self,
-        title: &str,
-        into_iter: I,
-        id_salt: &mut u64,
-        mut fun: F,
-    ) where
+    fn inspect_with_options<T: Inspect>(&mut self, what: &T, id: Id, options: InspectOptions) {
+        what.inspect(self, &InspectCtx::new(id, options));
+    }
+    fn inspect_iter_with<'a, I, T, F>(&mut self, title: &str, into_iter: I, id: Id, mut fun: F)
+    where
         I: IntoIterator<Item = &'a T>,
         T: 'a,
-        F: FnMut(&mut Ui, usize, &T, &mut u64),
+        F: FnMut(&mut Ui, usize, &T, Id),
     {
-        inspect_iter_with_body!(self, title, into_iter, id_salt, fun);
+        inspect_iter_with_body!(self, title, into_iter, id, fun);
     }
-    fn inspect_iter_with_mut<'a, I, T, F>(
-        &mut self,
-        title: &str,
-        into_iter: I,
-        id_salt: &mut u64,
-        mut fun: F,
-    ) where
+    fn inspect_iter_with_mut<'a, I, T, F>(&mut self, title: &str, into_iter: I, id: Id, mut fun: F)
+    where
         I: IntoIterator<Item = &'a mut T>,
         T: 'a,
-        F: FnMut(&mut Ui, usize, &mut T, &mut u64),
+        F: FnMut(&mut Ui, usize, &mut T, Id),
     {
-        inspect_iter_with_body!(self, title, into_iter, id_salt, fun);
+        inspect_iter_with_body!(self, title, into_iter, id, fun);
     }
-    fn inspect_mut<T: Inspect>(&mut self, what: &mut T, id_salt: &mut u64) {
-        what.inspect_mut(self, *id_salt);
-        *id_salt += 1;
+    fn inspect_mut<T: Inspect>(&mut self, what: &mut T, id: Id) -> InspectResponse {
+        self.inspect_mut_with_options(what, id, InspectOptions::default())
+    }
+    fn inspect_mut_with_options<T: Inspect>(
+        &mut self,
+        what: &mut T,
+        id: Id,
+        options: InspectOptions,
+    ) -> InspectResponse {
+        what.inspect_mut(self, &InspectCtx::new(id, options))
     }
-    fn property_mut<T: Inspect>(&mut self, name: &str, what: &mut T, id_salt: &mut u64) {
+    fn property_mut<T: Inspect>(&mut self, name: &str, what: &mut T, id: Id) -> InspectResponse {
         self.horizontal(|ui| {
             if ui
                 .add(egui::Label::new(name).sense(egui::Sense::click()))
                 .clicked()
             {
-                ui.output_mut(|o| o.copied_text = format!("{:?}", what));
+                copy_debug_text(ui, what);
             }
-            ui.inspect_mut(what, id_salt);
-        });
+            ui.inspect_mut(what, id)
+        })
+        .inner
     }
 }
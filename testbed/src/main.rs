@@ -1,46 +1,175 @@
 use {
     eframe::{egui, App, Frame, NativeOptions},
-    egui_inspect::{derive::Inspect, inspect, Inspect},
+    egui_inspect::{derive::Inspect, inspect, InspectOptions, InspectUnion, UiExt},
     rand::{distributions::Alphanumeric, prelude::SliceRandom, thread_rng, Rng},
     std::{fmt::Debug, marker::PhantomData},
 };
 
 struct Testbed {
+    settings: Settings,
+    /// Whether the settings were changed since they were last saved
+    settings_dirty: bool,
     entities: Vec<GameEntity>,
     some_string: String,
     list_of_strings: Vec<String>,
+    raw_value: RawValue,
 }
 
+/// A value as it might come from FFI, with every member valid for any bit pattern
+#[derive(Inspect, Clone, Copy)]
+#[repr(C)]
+#[inspect(unsafe_view_all_members)]
+union RawValue {
+    int: i32,
+    float: f32,
+    bytes: [u8; 4],
+}
+
+// SAFETY: All members are plain old data of the same size, so every byte is initialized,
+// and every member is valid for any bit pattern
+unsafe impl InspectUnion for RawValue {}
+
+impl Debug for RawValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // All members are plain old data of the same size
+        f.debug_tuple("RawValue")
+            .field(&unsafe { self.bytes })
+            .finish()
+    }
+}
+
+#[derive(Inspect, Debug)]
+#[inspect(tabs, default_open)]
+struct Settings {
+    #[inspect(group = "Video")]
+    fullscreen: bool,
+    #[inspect(group = "Video", range = 30..=240)]
+    max_fps: u32,
+    #[inspect(group = "Audio", range = 0.0..=1.0, speed = 0.01)]
+    volume: f32,
+    #[inspect(group = "Audio")]
+    muted: bool,
+    /// Shown above the tabs, as it's not in any group
+    player_name: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            max_fps: 60,
+            volume: 0.8,
+            muted: false,
+            player_name: "Player".into(),
+        }
+    }
+}
+
+/// An entity of the game world.
+///
+/// Entities are *validated* on every frame:
+///
+/// - The `name` must not be empty
+/// - Only entities in **godmode** can fly
 #[derive(Inspect, Debug)]
+#[inspect(
+    summary = "#{id} {name} ({hp} hp)",
+    validate = Self::validate,
+    button("Heal", Self::heal),
+    button("Respawn", Self::respawn),
+    property(name = "heading (degrees)", get = Self::heading_deg, set = Self::set_heading_deg),
+    property(name = "speed (km/h)", get = Self::speed_kmh)
+)]
 struct GameEntity {
+    /// Unique id of the entity. Assigned on creation, and never changed.
+    #[inspect(read_only)]
+    id: u32,
     /// The name of the game entity
+    #[inspect(order = -1, validate = non_empty)]
     name: String,
     /// A position vector
     ///
     /// Multiline explanation.
+    #[inspect(flatten, group = "Physics", on_change = Self::update_distance)]
     position: Vector2,
+    /// Distance from the origin, updated whenever the position changes
+    #[inspect(read_only, group = "Physics")]
+    distance: f32,
+    #[inspect(group = "Physics")]
+    velocity: Vector2,
+    /// `egui::Vec2` is a foreign type, inspected through a remote derive
+    #[inspect(group = "Physics", with = Vec2Def)]
+    acceleration: egui::Vec2,
+    #[inspect(rename = "Hit points", range = 0..=100, suffix = " hp", group = "Stats")]
     hp: i32,
+    /// Movement speed, adjustable in steps of `0.25`
+    #[inspect(
+        enabled_if = !self.flying,
+        range = 0.0..=10.0,
+        speed = 0.05,
+        step = 0.25,
+        decimals = 2,
+        group = "Stats"
+    )]
+    speed: f32,
+    #[inspect(group = "Stats")]
     godmode: bool,
+    /// Only available in godmode
+    #[inspect(group = "Stats", visible_if = self.godmode)]
+    flying: bool,
     dir: Dir,
+    #[inspect(display = "radio")]
+    shape: Shape,
+    color: Rgb,
     /// Something with an opaque representation
     ///
     /// We either can't, or don't want to impl `Inspect`
     /// for this field.
-    #[opaque]
+    #[inspect(opaque)]
     #[allow(dead_code)]
     something_opaque: MyOpaque,
-    /// A field with a custom inspect function
-    #[inspect_with(custom_inspect)]
+    /// A field with custom inspect functions
+    #[inspect(with = custom_inspect)]
     custom: MyOpaque,
+    /// Stored in radians, edited in degrees through a property
+    #[inspect(skip)]
+    heading: f32,
+    /// Not shown in the inspector at all
+    #[inspect(skip)]
+    #[allow(dead_code)]
+    cache: Vec<u8>,
     tuple: TupleStruct,
     generic: Generic<String>,
+    labeled: Labeled<u8>,
+    #[inspect(tooltip = "Zero-sized marker")]
     phantom: PhantomData<NonInspect>,
+    handle: Handle<NonInspect>,
     unit: (),
 }
 
+#[derive(Debug)]
 struct NonInspect;
 
+#[derive(Inspect)]
+#[inspect(remote = "egui::Vec2", inline)]
+struct Vec2Def {
+    #[inspect(speed = 0.01, prefix = "x: ")]
+    x: f32,
+    #[inspect(speed = 0.01, prefix = "y: ")]
+    y: f32,
+}
+
+#[derive(Debug)]
+struct Rgb {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+egui_inspect::impl_inspect_fields!(Rgb { r, g, b });
+
 #[derive(Inspect, Debug)]
+#[inspect(no_header)]
 struct TupleStruct(u32);
 
 #[derive(Default, Debug)]
@@ -51,60 +180,192 @@ struct MyOpaque {
 }
 
 #[derive(Inspect, Debug)]
-struct Generic<T: Inspect> {
+#[inspect(title = "Generic wrapper", default_open)]
+struct Generic<T> {
     field: T,
 }
 
-fn custom_inspect(o: &mut MyOpaque, ui: &mut egui::Ui, _id_source: u64) {
-    ui.collapsing("MyOpaque", |ui| {
-        inspect! {
-            ui,
-            "field 1": o.field1,
-            "field 2": o.field2,
-            "field 3": o.field3
-        }
-    });
+/// The flattened field requires `Generic<T>: InspectFields`, and the choice
+/// `Choice<T>: InspectEnum`, rather than bounds on `T`
+#[derive(Inspect, Debug)]
+struct Labeled<T> {
+    label: String,
+    #[inspect(flatten)]
+    inner: Generic<T>,
+    #[inspect(display = "radio")]
+    choice: Choice<T>,
+}
+
+#[derive(Inspect, Debug)]
+enum Choice<T> {
+    Nothing,
+    Something(T),
+}
+
+/// `T` is only used in `PhantomData`, so it's not required to implement `Inspect`
+#[derive(Inspect, Debug)]
+struct Handle<T> {
+    index: u32,
+    marker: PhantomData<T>,
+}
+
+mod custom_inspect {
+    use {
+        super::{egui, MyOpaque},
+        egui_inspect::{inspect, InspectCtx},
+    };
+
+    pub fn inspect(o: &MyOpaque, ui: &mut egui::Ui, ctx: &InspectCtx) {
+        egui::CollapsingHeader::new("MyOpaque")
+            .id_salt(ctx.id())
+            .show(ui, |ui| {
+                ui.label(format!("field 1: {}", o.field1));
+                ui.label(format!("field 2: {}", o.field2));
+                ui.label(format!("field 3: {}", o.field3));
+            });
+    }
+
+    pub fn inspect_mut(o: &mut MyOpaque, ui: &mut egui::Ui, ctx: &InspectCtx) {
+        egui::CollapsingHeader::new("MyOpaque")
+            .id_salt(ctx.id())
+            .show(ui, |ui| {
+                inspect! {
+                    ui,
+                    "field 1": o.field1,
+                    "field 2": o.field2,
+                    "field 3": o.field3
+                }
+            });
+    }
 }
 
 #[derive(Inspect, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+#[inspect(display = "toggle", show_discriminant)]
 enum Dir {
-    North,
+    /// Up on the map
+    North = 1,
     East,
     South,
     West,
 }
 
+#[derive(Inspect, Debug)]
+enum Shape {
+    /// A single point, without any extent
+    Point,
+    Circle {
+        /// The radius of the circle
+        r: f32,
+    },
+    #[inspect(rename = "Rectangle", tooltip = "Width and height")]
+    Rect(f32, f32),
+    /// Can't be switched to, because `NonDefault` has no `Default` impl
+    #[allow(dead_code)]
+    Special(#[inspect(opaque)] NonDefault),
+}
+
+#[derive(Debug)]
+struct NonDefault;
+
+fn non_empty(s: &str) -> Result<(), String> {
+    if s.is_empty() {
+        return Err("must not be empty".into());
+    }
+    Ok(())
+}
+
 impl GameEntity {
-    fn rand() -> Self {
+    fn validate(&self) -> Result<(), String> {
+        if self.flying && !self.godmode {
+            return Err("only entities in godmode can fly".into());
+        }
+        Ok(())
+    }
+
+    fn heal(&mut self) {
+        self.hp = 100;
+    }
+
+    fn respawn(&mut self) {
+        self.position = Vector2 { x: 0.0, y: 0.0 };
+        self.velocity = Vector2 { x: 0.0, y: 0.0 };
+        self.update_distance();
+    }
+
+    fn heading_deg(&self) -> f32 {
+        self.heading.to_degrees()
+    }
+
+    fn set_heading_deg(&mut self, deg: f32) {
+        self.heading = deg.rem_euclid(360.0).to_radians();
+    }
+
+    fn speed_kmh(&self) -> f32 {
+        self.speed * 3.6
+    }
+
+    fn update_distance(&mut self) {
+        self.distance = self.position.x.hypot(self.position.y);
+    }
+
+    fn rand(id: u32) -> Self {
         let mut rng = rand::thread_rng();
+        let position = Vector2::rand();
         let name_len = rng.gen_range(3..24);
         Self {
+            id,
             name: (&mut rng)
                 .sample_iter(&Alphanumeric)
                 .take(name_len)
                 .map(char::from)
                 .collect(),
-            position: Vector2::rand(),
+            distance: position.x.hypot(position.y),
+            position,
+            velocity: Vector2::rand(),
+            acceleration: egui::Vec2::ZERO,
             hp: rng.gen_range(0..100),
+            speed: 1.0,
             godmode: rng.gen(),
+            flying: false,
             dir: *[Dir::North, Dir::East, Dir::South, Dir::West]
                 .choose(&mut rng)
                 .unwrap(),
+            shape: Shape::Circle { r: rng.gen() },
+            color: Rgb {
+                r: rng.gen(),
+                g: rng.gen(),
+                b: rng.gen(),
+            },
+            heading: 0.0,
             something_opaque: MyOpaque::default(),
             custom: MyOpaque::default(),
+            cache: Vec::new(),
             tuple: TupleStruct(42),
             generic: Generic {
                 field: String::new(),
             },
+            labeled: Labeled {
+                label: "answer".into(),
+                inner: Generic { field: 42 },
+                choice: Choice::Something(7),
+            },
             phantom: PhantomData,
+            handle: Handle {
+                index: 0,
+                marker: PhantomData,
+            },
             unit: (),
         }
     }
 }
 
 #[derive(Inspect, Debug)]
+#[inspect(inline)]
 struct Vector2 {
+    #[inspect(speed = 0.01, prefix = "x: ")]
     x: f32,
+    #[inspect(speed = 0.01, prefix = "y: ")]
     y: f32,
 }
 
@@ -121,9 +382,12 @@ impl Vector2 {
 impl Default for Testbed {
     fn default() -> Self {
         Self {
-            entities: (0..100).map(|_| GameEntity::rand()).collect(),
+            settings: Settings::default(),
+            settings_dirty: false,
+            entities: (0..100).map(GameEntity::rand).collect(),
             some_string: "Hello world!".into(),
             list_of_strings: Vec::new(),
+            raw_value: RawValue { float: 1.5 },
         }
     }
 }
@@ -132,13 +396,38 @@ impl App for Testbed {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::vertical().show(ui, |ui| {
+                if ui
+                    .inspect_mut(&mut self.settings, egui::Id::new("settings"))
+                    .changed()
+                {
+                    self.settings_dirty = true;
+                }
+                if ui
+                    .add_enabled(self.settings_dirty, egui::Button::new("Save settings"))
+                    .clicked()
+                {
+                    self.settings_dirty = false;
+                }
                 inspect! {
                     ui,
                     self.some_string,
                     self.some_string.len(),
                     self.entities,
-                    self.list_of_strings
+                    self.list_of_strings,
+                    self.raw_value
                 }
+                ui.collapsing("Read-only view", |ui| {
+                    let options = InspectOptions {
+                        hex_ints: true,
+                        max_depth: Some(3),
+                        ..Default::default()
+                    };
+                    ui.inspect_with_options(
+                        &self.entities,
+                        egui::Id::new("read_only_entities"),
+                        options,
+                    );
+                });
             })
         });
 